@@ -0,0 +1,109 @@
+use super::AigerError;
+#[cfg(any(feature = "gzip", feature = "xz", feature = "bzip2"))]
+use std::io::Read;
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AigerCompression {
+    #[default]
+    None,
+    Gzip,
+    Xz,
+    Bzip2,
+}
+
+impl AigerCompression {
+    /// Guesses the compression format from the file extension, e.g. `.aig.gz`.
+    pub fn from_path<P: AsRef<Path>>(p: P) -> Self {
+        match p.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("gz") => Self::Gzip,
+            Some("xz") => Self::Xz,
+            Some("bz2") => Self::Bzip2,
+            _ => Self::None,
+        }
+    }
+
+    /// Detects the compression format from the leading magic bytes.
+    pub fn from_magic(buf: &[u8]) -> Self {
+        if buf.starts_with(&[0x1f, 0x8b]) {
+            Self::Gzip
+        } else if buf.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Self::Xz
+        } else if buf.starts_with(b"BZh") {
+            Self::Bzip2
+        } else {
+            Self::None
+        }
+    }
+
+    fn unsupported(self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{self:?} compression support is not enabled"),
+        )
+    }
+
+    pub(super) fn decompress(self, buf: &[u8]) -> io::Result<Vec<u8>> {
+        #[allow(unused_mut)]
+        let mut res = Vec::new();
+        match self {
+            Self::None => res.extend_from_slice(buf),
+            #[cfg(feature = "gzip")]
+            Self::Gzip => {
+                flate2::read::MultiGzDecoder::new(buf).read_to_end(&mut res)?;
+            }
+            #[cfg(feature = "xz")]
+            Self::Xz => {
+                xz2::read::XzDecoder::new_multi_decoder(buf).read_to_end(&mut res)?;
+            }
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2 => {
+                bzip2::read::MultiBzDecoder::new(buf).read_to_end(&mut res)?;
+            }
+            #[allow(unreachable_patterns)]
+            _ => return Err(self.unsupported()),
+        }
+        Ok(res)
+    }
+
+    /// Runs `write` on a compressing writer wrapped around `w` and finishes the
+    /// compressed stream.
+    pub(super) fn compress<W: Write>(
+        self,
+        w: W,
+        write: impl FnOnce(&mut dyn Write) -> Result<(), AigerError>,
+    ) -> Result<(), AigerError> {
+        match self {
+            Self::None => {
+                let mut w = w;
+                write(&mut w)
+            }
+            #[cfg(feature = "gzip")]
+            Self::Gzip => {
+                let mut e = flate2::write::GzEncoder::new(w, flate2::Compression::default());
+                write(&mut e)?;
+                e.finish()?;
+                Ok(())
+            }
+            #[cfg(feature = "xz")]
+            Self::Xz => {
+                let mut e = xz2::write::XzEncoder::new(w, 6);
+                write(&mut e)?;
+                e.finish()?;
+                Ok(())
+            }
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2 => {
+                let mut e = bzip2::write::BzEncoder::new(w, bzip2::Compression::default());
+                write(&mut e)?;
+                e.finish()?;
+                Ok(())
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported().into()),
+        }
+    }
+}
@@ -0,0 +1,590 @@
+use super::AigerError;
+use crate::{Aig, AigEdge, AigLatch, AigNode, AigNodeType, AigPropKind};
+use giputils::hash::GHashMap;
+use std::fmt::Display;
+
+/// Largest number of inputs accepted in a binary file.
+const MAX_BINARY_INPUTS: u32 = 1 << 26;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Def {
+    Undef,
+    Input,
+    Latch,
+    And(usize),
+}
+
+#[derive(Default)]
+struct AigerRaw {
+    /// Inputs listed in an ASCII file.
+    inputs: Vec<u32>,
+    /// Number of inputs of a binary file, which are implicitly `2, 4, ...`.
+    binary_inputs: u32,
+    latchs: Vec<(u32, u32, u32)>,
+    outputs: Vec<u32>,
+    bads: Vec<u32>,
+    constraints: Vec<u32>,
+    justice: Vec<Vec<u32>>,
+    fairness: Vec<u32>,
+    ands: Vec<(u32, u32, u32)>,
+    symbols: Vec<(u8, usize, String)>,
+    comments: Vec<String>,
+}
+
+struct Parser<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            pos: 0,
+            line: 1,
+        }
+    }
+
+    fn error<T>(&self, msg: impl Display) -> Result<T, AigerError> {
+        Err(AigerError::Parse {
+            line: self.line,
+            byte: self.pos,
+            message: msg.to_string(),
+        })
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.buf.get(self.pos).copied()
+    }
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == b'\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn expect(&mut self, ch: u8) -> Result<(), AigerError> {
+        match self.peek() {
+            Some(c) if c == ch => {
+                self.next();
+                Ok(())
+            }
+            Some(c) => self.error(format!("expected {:?} but got {:?}", ch as char, c as char)),
+            None => self.error(format!("expected {:?} but got end of file", ch as char)),
+        }
+    }
+
+    fn read_u32(&mut self) -> Result<u32, AigerError> {
+        let start = self.pos;
+        let mut res: u32 = 0;
+        while let Some(c @ b'0'..=b'9') = self.peek() {
+            let Some(r) = res
+                .checked_mul(10)
+                .and_then(|r| r.checked_add((c - b'0') as u32))
+            else {
+                return self.error("number too large");
+            };
+            res = r;
+            self.next();
+        }
+        if start == self.pos {
+            return self.error("expected unsigned integer");
+        }
+        Ok(res)
+    }
+
+    fn read_lit(&mut self, maxvar: u32) -> Result<u32, AigerError> {
+        let lit = self.read_u32()?;
+        if lit >> 1 > maxvar {
+            return self.error(format!(
+                "literal {lit} exceeds maximum variable index {maxvar}"
+            ));
+        }
+        Ok(lit)
+    }
+
+    fn read_lit_line(&mut self, maxvar: u32) -> Result<u32, AigerError> {
+        let lit = self.read_lit(maxvar)?;
+        self.expect(b'\n')?;
+        Ok(lit)
+    }
+
+    fn read_def_lit(&mut self, maxvar: u32) -> Result<u32, AigerError> {
+        let lit = self.read_lit(maxvar)?;
+        if lit < 2 || lit & 1 == 1 {
+            return self.error(format!("expected positive variable literal but got {lit}"));
+        }
+        Ok(lit)
+    }
+
+    fn read_delta(&mut self) -> Result<u32, AigerError> {
+        let mut res: u32 = 0;
+        for i in 0..5 {
+            let Some(c) = self.next() else {
+                return self.error("unexpected end of file in binary and gate");
+            };
+            let bits = (c & 0x7f) as u32;
+            if i == 4 && bits > 0xf {
+                return self.error("invalid binary delta encoding");
+            }
+            res |= bits << (7 * i);
+            if c & 0x80 == 0 {
+                return Ok(res);
+            }
+        }
+        self.error("invalid binary delta encoding")
+    }
+
+    fn read_line(&mut self) -> String {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c == b'\n' {
+                break;
+            }
+            self.next();
+        }
+        let line = String::from_utf8_lossy(&self.buf[start..self.pos]).into_owned();
+        self.next();
+        line
+    }
+
+    fn parse(mut self) -> Result<AigerRaw, AigerError> {
+        let binary = match self.buf.get(..3) {
+            Some(b"aag") => false,
+            Some(b"aig") => true,
+            _ => return self.error("expected 'aag' or 'aig' header"),
+        };
+        self.pos = 3;
+        let mut header = [0u32; 9];
+        for h in header.iter_mut().take(5) {
+            self.expect(b' ')?;
+            *h = self.read_u32()?;
+        }
+        for h in header.iter_mut().skip(5) {
+            if self.peek() != Some(b' ') {
+                break;
+            }
+            self.next();
+            *h = self.read_u32()?;
+        }
+        self.expect(b'\n')?;
+        let [m, i, l, o, a, b, c, j, f] = header;
+        let defined = i as u64 + l as u64 + a as u64;
+        if m >= u32::MAX >> 1 {
+            return self.error(format!("maximum variable index {m} too large"));
+        }
+        if binary && defined != m as u64 {
+            return self.error(format!(
+                "invalid maximum variable index {m} in binary format"
+            ));
+        }
+        if defined > m as u64 {
+            return self.error(format!("maximum variable index {m} less than I + L + A"));
+        }
+        // Every listed item takes at least a digit and a separator per
+        // literal, so counts that cannot fit in the rest of the file are
+        // rejected before anything is allocated for them.
+        let (input_len, latch_len, and_len) = if binary { (0, 2, 2) } else { (2, 4, 6) };
+        let props = o as u64 + b as u64 + c as u64 + j as u64 + f as u64;
+        let need = i as u64 * input_len + l as u64 * latch_len + props * 2 + a as u64 * and_len;
+        if need > (self.buf.len() - self.pos) as u64 {
+            return self.error("header counts exceed the file size");
+        }
+        // Binary inputs take no space in the file, so their number is only
+        // bounded by a fixed cap.
+        if binary && i > MAX_BINARY_INPUTS {
+            return self.error(format!(
+                "{i} inputs exceed the limit of {MAX_BINARY_INPUTS}"
+            ));
+        }
+        let mut raw = AigerRaw::default();
+        if binary {
+            raw.binary_inputs = i;
+        } else {
+            raw.inputs.reserve(i as usize);
+            for _ in 0..i {
+                raw.inputs.push(self.read_def_lit(m)?);
+                self.expect(b'\n')?;
+            }
+        }
+        raw.latchs.reserve(l as usize);
+        for k in 0..l {
+            let lit = if binary {
+                2 * (i + k + 1)
+            } else {
+                let lit = self.read_def_lit(m)?;
+                self.expect(b' ')?;
+                lit
+            };
+            let next = self.read_lit(m)?;
+            let reset = if self.peek() == Some(b' ') {
+                self.next();
+                self.read_lit(m)?
+            } else {
+                0
+            };
+            self.expect(b'\n')?;
+            raw.latchs.push((lit, next, reset));
+        }
+        for _ in 0..o {
+            raw.outputs.push(self.read_lit_line(m)?);
+        }
+        for _ in 0..b {
+            raw.bads.push(self.read_lit_line(m)?);
+        }
+        for _ in 0..c {
+            raw.constraints.push(self.read_lit_line(m)?);
+        }
+        let mut justice_size = Vec::new();
+        for _ in 0..j {
+            let size = self.read_u32()?;
+            if size == 0 {
+                return self.error("empty justice property");
+            }
+            self.expect(b'\n')?;
+            justice_size.push(size);
+        }
+        for size in justice_size {
+            let mut js = Vec::new();
+            for _ in 0..size {
+                js.push(self.read_lit_line(m)?);
+            }
+            raw.justice.push(js);
+        }
+        for _ in 0..f {
+            raw.fairness.push(self.read_lit_line(m)?);
+        }
+        raw.ands.reserve(a as usize);
+        for k in 0..a {
+            if binary {
+                let lhs = 2 * (i + l + k + 1);
+                let delta0 = self.read_delta()?;
+                let delta1 = self.read_delta()?;
+                if delta0 == 0 || delta0 > lhs || delta1 > lhs - delta0 {
+                    return self.error(format!("invalid binary and gate {lhs}"));
+                }
+                let rhs0 = lhs - delta0;
+                let rhs1 = rhs0 - delta1;
+                raw.ands.push((lhs, rhs0, rhs1));
+            } else {
+                let lhs = self.read_def_lit(m)?;
+                self.expect(b' ')?;
+                let rhs0 = self.read_lit(m)?;
+                self.expect(b' ')?;
+                let rhs1 = self.read_lit_line(m)?;
+                raw.ands.push((lhs, rhs0, rhs1));
+            }
+        }
+        while let Some(t) = self.peek() {
+            if t == b'c' && matches!(self.buf.get(self.pos + 1), Some(b'\n') | None) {
+                self.next();
+                self.next();
+                while self.peek().is_some() {
+                    raw.comments.push(self.read_line());
+                }
+                break;
+            }
+            let num = match t {
+                b'i' => i,
+                b'l' => l,
+                b'o' => o,
+                b'b' => b,
+                b'c' => c,
+                b'j' => j,
+                b'f' => f,
+                _ => return self.error("invalid symbol table entry"),
+            };
+            self.next();
+            let idx = self.read_u32()?;
+            if idx >= num {
+                return self.error(format!("symbol index {idx} out of range"));
+            }
+            self.expect(b' ')?;
+            let name = self.read_line();
+            raw.symbols.push((t, idx as usize, name));
+        }
+        Ok(raw)
+    }
+}
+
+impl AigerRaw {
+    fn input_lits(&self) -> impl Iterator<Item = u32> + '_ {
+        let binary = (1..=self.binary_inputs).map(|v| 2 * v);
+        self.inputs.iter().copied().chain(binary)
+    }
+
+    fn build(self) -> Result<Aig, AigerError> {
+        // Variables above the largest one used need no entry, the header may
+        // declare far more than the file defines.
+        let maxvar = self
+            .input_lits()
+            .chain(
+                self.latchs
+                    .iter()
+                    .flat_map(|&(lit, next, reset)| [lit, next, reset]),
+            )
+            .chain(
+                self.ands
+                    .iter()
+                    .flat_map(|&(lhs, rhs0, rhs1)| [lhs, rhs0, rhs1]),
+            )
+            .chain(self.outputs.iter().copied())
+            .chain(self.bads.iter().copied())
+            .chain(self.constraints.iter().copied())
+            .chain(self.justice.iter().flatten().copied())
+            .chain(self.fairness.iter().copied())
+            .map(|lit| (lit >> 1) as usize)
+            .max()
+            .unwrap_or(0);
+        let mut def = vec![Def::Undef; maxvar + 1];
+        let mut define = |lit: u32, d: Def| {
+            let var = (lit >> 1) as usize;
+            if def[var] != Def::Undef {
+                return Err(AigerError::Invalid(format!("variable {var} defined twice")));
+            }
+            def[var] = d;
+            Ok(())
+        };
+        for lit in self.input_lits() {
+            define(lit, Def::Input)?;
+        }
+        for &(lit, ..) in self.latchs.iter() {
+            define(lit, Def::Latch)?;
+        }
+        for (k, &(lhs, ..)) in self.ands.iter().enumerate() {
+            define(lhs, Def::And(k))?;
+        }
+        let used = self
+            .latchs
+            .iter()
+            .flat_map(|&(_, next, reset)| [next, reset])
+            .chain(self.ands.iter().flat_map(|&(_, rhs0, rhs1)| [rhs0, rhs1]))
+            .chain(self.outputs.iter().copied())
+            .chain(self.bads.iter().copied())
+            .chain(self.constraints.iter().copied())
+            .chain(self.justice.iter().flatten().copied())
+            .chain(self.fairness.iter().copied());
+        for lit in used {
+            if lit > 1 && def[(lit >> 1) as usize] == Def::Undef {
+                return Err(AigerError::Invalid(format!("literal {lit} undefined")));
+            }
+        }
+        let num_inputs = self.inputs.len() + self.binary_inputs as usize;
+        let ordered = num_inputs + self.latchs.len() + self.ands.len() == maxvar
+            && self
+                .ands
+                .iter()
+                .all(|&(lhs, rhs0, rhs1)| rhs0 >> 1 < lhs >> 1 && rhs1 >> 1 < lhs >> 1);
+        let order: Vec<usize> = if ordered {
+            (1..=maxvar).collect()
+        } else {
+            self.topo_order(&def)?
+        };
+        let mut map = vec![0; maxvar + 1];
+        for (k, &v) in order.iter().enumerate() {
+            map[v] = k + 1;
+        }
+        let edge = |lit: u32| AigEdge::new(map[(lit >> 1) as usize], lit & 1 == 1);
+        let mut nodes = Vec::with_capacity(order.len() + 1);
+        nodes.push(AigNode {
+            id: 0,
+            typ: AigNodeType::False,
+        });
+        for &v in order.iter() {
+            let id = nodes.len();
+            let node = match def[v] {
+                Def::And(k) => {
+                    let (_, rhs0, rhs1) = self.ands[k];
+                    AigNode::new_and(id, edge(rhs0), edge(rhs1))
+                }
+                _ => AigNode {
+                    id,
+                    typ: AigNodeType::Leaf,
+                },
+            };
+            nodes.push(node);
+        }
+        let inputs: Vec<usize> = self.input_lits().map(|lit| edge(lit).node_id()).collect();
+        let mut latchs = Vec::with_capacity(self.latchs.len());
+        for &(lit, next, reset) in self.latchs.iter() {
+            let init = if reset >> 1 == lit >> 1 {
+                if reset != lit {
+                    return Err(AigerError::Invalid(format!(
+                        "invalid reset literal {reset} of latch {lit}"
+                    )));
+                }
+                None
+            } else {
+                Some(edge(reset))
+            };
+            latchs.push(AigLatch::new(edge(lit).node_id(), edge(next), init));
+        }
+        let mut symbols = GHashMap::new();
+        let mut prop_symbols = GHashMap::new();
+        for (typ, idx, name) in self.symbols {
+            let kind = match typ {
+                b'i' => {
+                    symbols.insert(inputs[idx], name);
+                    continue;
+                }
+                b'l' => {
+                    symbols.insert(latchs[idx].input, name);
+                    continue;
+                }
+                b'o' => AigPropKind::Output,
+                b'b' => AigPropKind::Bad,
+                b'c' => AigPropKind::Constraint,
+                b'j' => AigPropKind::Justice,
+                _ => AigPropKind::Fairness,
+            };
+            prop_symbols.insert((kind, idx), name);
+        }
+        let edges = |lits: &[u32]| lits.iter().map(|&lit| edge(lit)).collect();
+        Ok(Aig {
+            nodes,
+            inputs,
+            latchs,
+            outputs: edges(&self.outputs),
+            bads: edges(&self.bads),
+            constraints: edges(&self.constraints),
+            justice: self.justice.iter().map(|j| edges(j)).collect(),
+            fairness: edges(&self.fairness),
+            symbols,
+            prop_symbols,
+            comments: self.comments,
+            strash_table: None,
+        })
+    }
+
+    /// Orders the defined variables as inputs, latches and then and gates in
+    /// topological order, for ASCII files whose and gates are not sorted.
+    fn topo_order(&self, def: &[Def]) -> Result<Vec<usize>, AigerError> {
+        let mut order: Vec<usize> = self
+            .input_lits()
+            .chain(self.latchs.iter().map(|&(lit, ..)| lit))
+            .map(|lit| (lit >> 1) as usize)
+            .collect();
+        // 0: unvisited, 1: visiting, 2: visited
+        let mut state = vec![0u8; def.len()];
+        let mut stack = Vec::new();
+        for &(lhs, ..) in self.ands.iter() {
+            stack.push(((lhs >> 1) as usize, false));
+            while let Some((v, expanded)) = stack.pop() {
+                if expanded {
+                    state[v] = 2;
+                    order.push(v);
+                    continue;
+                }
+                match state[v] {
+                    2 => continue,
+                    1 => {
+                        return Err(AigerError::Invalid(format!(
+                            "cyclic definition of variable {v}"
+                        )));
+                    }
+                    _ => (),
+                }
+                state[v] = 1;
+                stack.push((v, true));
+                let Def::And(k) = def[v] else { unreachable!() };
+                let (_, rhs0, rhs1) = self.ands[k];
+                for u in [(rhs1 >> 1) as usize, (rhs0 >> 1) as usize] {
+                    if !matches!(def[u], Def::And(_)) {
+                        continue;
+                    }
+                    match state[u] {
+                        0 => stack.push((u, false)),
+                        1 => {
+                            return Err(AigerError::Invalid(format!(
+                                "cyclic definition of variable {u}"
+                            )));
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        Ok(order)
+    }
+}
+
+pub(super) fn parse_aiger(buf: &[u8]) -> Result<Aig, AigerError> {
+    Parser::new(buf).parse()?.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_binary() {
+        let aag = b"aag 7 2 1 2 4 1\n2\n4\n6 13 1\n6\n7\n14\n8 2 6\n10 4 7\n12 9 11\n14 2 4\ni0 x\nl0 s\nb0 p\nc\nhello\n";
+        let aig =
+            b"aig 7 2 1 2 4 1\n13 1\n6\n7\n14\n\x02\x04\x03\x03\x01\x02\x0a\x02i0 x\nl0 s\nb0 p\n";
+        let aag = parse_aiger(aag).unwrap();
+        let aig = parse_aiger(aig).unwrap();
+        for a in [&aag, &aig] {
+            assert_eq!(a.inputs, vec![1, 2]);
+            assert_eq!(a.latchs[0].input, 3);
+            assert_eq!(a.latchs[0].init, Some(AigEdge::constant(true)));
+            assert_eq!(a.latchs[0].next, AigEdge::new(6, true));
+            assert_eq!(a.bads, vec![AigEdge::new(7, false)]);
+            assert_eq!(
+                a.nodes[6].fanin(),
+                (AigEdge::new(4, true), AigEdge::new(5, true))
+            );
+            assert_eq!(a.get_symbol(1).as_deref(), Some("x"));
+            assert_eq!(a.get_symbol(3).as_deref(), Some("s"));
+            assert_eq!(a.get_prop_symbol(AigPropKind::Bad, 0).as_deref(), Some("p"));
+        }
+        assert_eq!(aag.comments, vec!["hello".to_string()]);
+    }
+
+    #[test]
+    fn test_unordered() {
+        let aag = b"aag 5 1 0 1 2\n8\n4\n4 10 9\n10 8 1\n";
+        let aig = parse_aiger(aag).unwrap();
+        assert_eq!(aig.inputs, vec![1]);
+        assert_eq!(
+            aig.nodes[2].fanin(),
+            (AigEdge::constant(true), AigEdge::new(1, false))
+        );
+        assert_eq!(
+            aig.nodes[3].fanin(),
+            (AigEdge::new(1, true), AigEdge::new(2, false))
+        );
+        assert_eq!(aig.outputs, vec![AigEdge::new(3, false)]);
+        assert!(matches!(
+            parse_aiger(b"aag 2 0 0 0 2\n2 4 1\n4 2 1\n"),
+            Err(AigerError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse_aiger(b"aag 1 0 0 1 0\n4\n"),
+            Err(AigerError::Parse { line: 2, .. })
+        ));
+        assert!(parse_aiger(b"aig 2 1 0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn test_huge_header() {
+        assert!(parse_aiger(b"aag 1000000000 0 0 0 0\n").is_ok());
+        assert!(parse_aiger(b"aag 1000 0 0 0 0\n").is_ok());
+        assert!(parse_aiger(b"aig 100 100 0 0 0\n").is_ok());
+        assert!(parse_aiger(b"aig 1000000000 1000000000 0 0 0\n").is_err());
+        assert!(parse_aiger(b"aig 1000000000 0 1000000000 0 0\n").is_err());
+        assert!(parse_aiger(b"aag 1000000000 0 0 0 1000000000\n").is_err());
+        assert!(parse_aiger(b"aag 3 0 0 0 0 1000000000 1000000000\n").is_err());
+    }
+
+    #[test]
+    fn test_comment_marker_at_eof() {
+        let aig = parse_aiger(b"aag 1 1 0 0 0\n2\nc").unwrap();
+        assert!(aig.comments.is_empty());
+    }
+}
@@ -0,0 +1,188 @@
+use super::{AigerError, AigerMode};
+use crate::{Aig, AigEdge, AigPropKind};
+use std::io::Write;
+
+struct Writer<'a> {
+    aig: &'a Aig,
+    map: Vec<usize>,
+    maxvar: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn new(aig: &'a Aig, mode: AigerMode) -> Result<Self, AigerError> {
+        let maxvar;
+        let map = match mode {
+            AigerMode::Ascii => {
+                maxvar = aig.num_nodes() - 1;
+                aig.nodes_range_with_false().collect()
+            }
+            AigerMode::Binary => {
+                let mut map = vec![usize::MAX; aig.num_nodes()];
+                map[0] = 0;
+                let mut var = 0;
+                for &id in aig
+                    .inputs
+                    .iter()
+                    .chain(aig.latchs.iter().map(|l| &l.input))
+                    .chain(aig.ands_iter().map(|n| &n.id))
+                {
+                    var += 1;
+                    map[id] = var;
+                }
+                maxvar = var;
+                map
+            }
+        };
+        let res = Self { aig, map, maxvar };
+        if mode == AigerMode::Ascii {
+            return Ok(res);
+        }
+        for n in aig.ands_iter() {
+            let (fanin0, fanin1) = n.fanin();
+            let lhs = res.lit(n.id.into())?;
+            if res.lit(fanin0)? >= lhs || res.lit(fanin1)? >= lhs {
+                return Err(AigerError::Invalid(format!(
+                    "and node {} is not in topological order",
+                    n.id
+                )));
+            }
+        }
+        Ok(res)
+    }
+
+    fn lit(&self, e: AigEdge) -> Result<usize, AigerError> {
+        match self.map[e.node_id()] {
+            usize::MAX => Err(AigerError::Invalid(format!(
+                "node {} is neither an input nor a latch",
+                e.node_id()
+            ))),
+            var => Ok(var * 2 + e.compl() as usize),
+        }
+    }
+
+    fn write_lits<'b>(
+        &self,
+        w: &mut impl Write,
+        edges: impl IntoIterator<Item = &'b AigEdge>,
+    ) -> Result<(), AigerError> {
+        for e in edges {
+            writeln!(w, "{}", self.lit(*e)?)?;
+        }
+        Ok(())
+    }
+
+    fn write_delta(w: &mut impl Write, mut x: usize) -> Result<(), AigerError> {
+        while x & !0x7f != 0 {
+            w.write_all(&[(x & 0x7f) as u8 | 0x80])?;
+            x >>= 7;
+        }
+        w.write_all(&[x as u8])?;
+        Ok(())
+    }
+
+    fn write(&self, w: &mut impl Write, mode: AigerMode) -> Result<(), AigerError> {
+        let aig = self.aig;
+        let binary = mode == AigerMode::Binary;
+        let num_ands = aig.ands_iter().count();
+        write!(
+            w,
+            "{} {} {} {} {} {}",
+            if binary { "aig" } else { "aag" },
+            self.maxvar,
+            aig.inputs.len(),
+            aig.latchs.len(),
+            aig.outputs.len(),
+            num_ands
+        )?;
+        let opt = [
+            aig.bads.len(),
+            aig.constraints.len(),
+            aig.justice.len(),
+            aig.fairness.len(),
+        ];
+        if let Some(last) = opt.iter().rposition(|&n| n > 0) {
+            for n in &opt[..=last] {
+                write!(w, " {n}")?;
+            }
+        }
+        writeln!(w)?;
+        if !binary {
+            for &i in aig.inputs.iter() {
+                writeln!(w, "{}", self.lit(i.into())?)?;
+            }
+        }
+        for l in aig.latchs.iter() {
+            let lit = self.lit(l.input.into())?;
+            if !binary {
+                write!(w, "{lit} ")?;
+            }
+            let reset = match l.init {
+                Some(init) => self.lit(init)?,
+                None => lit,
+            };
+            write!(w, "{}", self.lit(l.next)?)?;
+            if reset != 0 {
+                write!(w, " {reset}")?;
+            }
+            writeln!(w)?;
+        }
+        self.write_lits(w, &aig.outputs)?;
+        self.write_lits(w, &aig.bads)?;
+        self.write_lits(w, &aig.constraints)?;
+        for j in aig.justice.iter() {
+            writeln!(w, "{}", j.len())?;
+        }
+        self.write_lits(w, aig.justice.iter().flatten())?;
+        self.write_lits(w, &aig.fairness)?;
+        for n in aig.ands_iter() {
+            let lhs = self.lit(n.id.into())?;
+            let (fanin0, fanin1) = n.fanin();
+            let (fanin0, fanin1) = (self.lit(fanin0)?, self.lit(fanin1)?);
+            let (rhs0, rhs1) = (fanin0.max(fanin1), fanin0.min(fanin1));
+            if binary {
+                Self::write_delta(w, lhs - rhs0)?;
+                Self::write_delta(w, rhs0 - rhs1)?;
+            } else {
+                writeln!(w, "{lhs} {rhs0} {rhs1}")?;
+            }
+        }
+        for (k, i) in aig.inputs.iter().enumerate() {
+            if let Some(s) = aig.symbols.get(i) {
+                writeln!(w, "i{k} {s}")?;
+            }
+        }
+        for (k, l) in aig.latchs.iter().enumerate() {
+            if let Some(s) = aig.symbols.get(&l.input) {
+                writeln!(w, "l{k} {s}")?;
+            }
+        }
+        for (kind, c, num) in [
+            (AigPropKind::Output, 'o', aig.outputs.len()),
+            (AigPropKind::Bad, 'b', aig.bads.len()),
+            (AigPropKind::Constraint, 'c', aig.constraints.len()),
+            (AigPropKind::Justice, 'j', aig.justice.len()),
+            (AigPropKind::Fairness, 'f', aig.fairness.len()),
+        ] {
+            for k in 0..num {
+                if let Some(s) = aig.prop_symbols.get(&(kind, k)) {
+                    writeln!(w, "{c}{k} {s}")?;
+                }
+            }
+        }
+        if !aig.comments.is_empty() {
+            writeln!(w, "c")?;
+            for c in aig.comments.iter() {
+                writeln!(w, "{c}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn write_aiger(
+    aig: &Aig,
+    w: &mut impl Write,
+    mode: AigerMode,
+) -> Result<(), AigerError> {
+    Writer::new(aig, mode)?.write(w, mode)
+}
@@ -0,0 +1,178 @@
+use crate::{Aig, AigEdge};
+
+/// Two-valued word-parallel simulator. Every node carries `words` machine
+/// words, so `64 * words` patterns are simulated at once.
+pub struct BitSimulate<'a> {
+    aig: &'a Aig,
+    words: usize,
+    state: Vec<u64>,
+    value: Vec<u64>,
+    rng: u64,
+}
+
+impl<'a> BitSimulate<'a> {
+    pub fn new(aig: &'a Aig, words: usize, seed: u64) -> Self {
+        assert!(words > 0);
+        let mut res = Self {
+            aig,
+            words,
+            state: vec![0; aig.latchs.len() * words],
+            value: vec![0; aig.num_nodes() * words],
+            rng: seed,
+        };
+        res.reset();
+        res
+    }
+
+    #[inline]
+    pub fn words(&self) -> usize {
+        self.words
+    }
+
+    /// splitmix64
+    #[inline]
+    fn random(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Puts every latch back to its initial value. Uninitialized latches get
+    /// random values, and gate-defined inits are evaluated under new random
+    /// inputs, which are kept as the inputs of the first cycle.
+    pub fn reset(&mut self) {
+        let w = self.words;
+        let mut gate_init = Vec::new();
+        for (i, l) in self.aig.latchs.iter().enumerate() {
+            match l.init {
+                Some(init) if init.is_const() => {
+                    let v = if init.to_constant() { !0 } else { 0 };
+                    self.state[i * w..(i + 1) * w].fill(v);
+                }
+                init => {
+                    if let Some(init) = init {
+                        gate_init.push((i, init));
+                    }
+                    for k in 0..w {
+                        self.state[i * w + k] = self.random();
+                    }
+                }
+            }
+        }
+        self.randomize_inputs();
+        if !gate_init.is_empty() {
+            self.simulate();
+            for (i, init) in gate_init {
+                for k in 0..w {
+                    self.state[i * w + k] = self.value(init, k);
+                }
+            }
+        }
+    }
+
+    pub fn randomize_inputs(&mut self) {
+        let w = self.words;
+        for &i in self.aig.inputs.iter() {
+            for k in 0..w {
+                self.value[i * w + k] = self.random();
+            }
+        }
+    }
+
+    /// Sets the `i`-th input to the given `words` words.
+    pub fn set_input(&mut self, i: usize, value: &[u64]) {
+        let w = self.words;
+        let id = self.aig.inputs[i];
+        self.value[id * w..(id + 1) * w].copy_from_slice(value);
+    }
+
+    /// Evaluates all nodes under the current inputs and latch state.
+    pub fn simulate(&mut self) {
+        let w = self.words;
+        self.value[..w].fill(0);
+        for (i, l) in self.aig.latchs.iter().enumerate() {
+            self.value[l.input * w..(l.input + 1) * w]
+                .copy_from_slice(&self.state[i * w..(i + 1) * w]);
+        }
+        for node in self.aig.ands_iter() {
+            let (fanin0, fanin1) = node.fanin();
+            let (prev, cur) = self.value.split_at_mut(node.node_id() * w);
+            let v0 = &prev[fanin0.node_id() * w..(fanin0.node_id() + 1) * w];
+            let v1 = &prev[fanin1.node_id() * w..(fanin1.node_id() + 1) * w];
+            let m0 = if fanin0.compl() { !0 } else { 0 };
+            let m1 = if fanin1.compl() { !0 } else { 0 };
+            for ((c, a), b) in cur[..w].iter_mut().zip(v0).zip(v1) {
+                *c = (a ^ m0) & (b ^ m1);
+            }
+        }
+    }
+
+    /// Moves latches to their next-state values computed by `simulate`.
+    pub fn step(&mut self) {
+        let w = self.words;
+        for (i, l) in self.aig.latchs.iter().enumerate() {
+            for k in 0..w {
+                self.state[i * w + k] = self.value(l.next, k);
+            }
+        }
+    }
+
+    #[inline]
+    pub fn value(&self, e: AigEdge, word: usize) -> u64 {
+        let v = self.value[e.node_id() * self.words + word];
+        if e.compl() { !v } else { v }
+    }
+
+    #[inline]
+    pub fn node_value(&self, n: usize) -> &[u64] {
+        &self.value[n * self.words..(n + 1) * self.words]
+    }
+}
+
+impl Aig {
+    /// Simulates `64 * words` random input sequences of `cycles` cycles from
+    /// the initial state, returning for each node its signature of
+    /// `cycles * words` words.
+    pub fn random_simulate(&self, words: usize, cycles: usize, seed: u64) -> Vec<Vec<u64>> {
+        let mut sim = BitSimulate::new(self, words, seed);
+        let mut sig = vec![Vec::with_capacity(cycles * words); self.num_nodes()];
+        for c in 0..cycles {
+            if c > 0 {
+                sim.randomize_inputs();
+            }
+            sim.simulate();
+            for (n, s) in sig.iter_mut().enumerate() {
+                s.extend_from_slice(sim.node_value(n));
+            }
+            sim.step();
+        }
+        sig
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_simulate() {
+        let mut aig = Aig::new();
+        let i: AigEdge = aig.new_input().into();
+        let l = aig.new_leaf_node();
+        aig.add_latch(l, !AigEdge::from(l), Some(AigEdge::constant(false)));
+        let x = aig.trivial_new_and_node(i, !i);
+        let y = aig.new_and_node(i, l.into());
+        let sig = aig.random_simulate(2, 3, 0);
+        assert_eq!(sig[l], vec![0, 0, !0, !0, 0, 0]);
+        assert!(sig[x.node_id()].iter().all(|v| *v == 0));
+        for ((y, i), l) in sig[y.node_id()].iter().zip(&sig[i.node_id()]).zip(&sig[l]) {
+            assert_eq!(*y, i & l);
+        }
+        let g = aig.new_leaf_node();
+        aig.add_latch(g, g.into(), Some(i));
+        let sig = aig.random_simulate(2, 2, 0);
+        assert_eq!(sig[g][..2], sig[i.node_id()][..2]);
+    }
+}
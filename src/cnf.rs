@@ -1,6 +1,8 @@
+mod cut;
+
 use crate::{Aig, AigEdge};
 use giputils::hash::GHashSet;
-use logicrs::{DagCnf, LitVvec, Var};
+use logicrs::{DagCnf, Lit, LitVec, LitVvec, Var, VarVMap};
 
 impl Aig {
     #[inline]
@@ -118,4 +120,200 @@ impl Aig {
         }
         ans
     }
+
+    /// Tseitin relation of the and node `n` together with the edges it is
+    /// expressed over.
+    fn node_rel(&self, n: usize, optimize: bool) -> (LitVvec, Vec<AigEdge>) {
+        let lit = Var::new(n).lit();
+        if optimize {
+            if let Some((xor0, xor1)) = self.is_xor(n) {
+                let rel = LitVvec::cnf_xor(lit, xor0.to_lit(), xor1.to_lit());
+                return (rel, vec![xor0, xor1]);
+            }
+            if let Some((c, t, e)) = self.is_ite(n) {
+                let rel = LitVvec::cnf_ite(lit, c.to_lit(), t.to_lit(), e.to_lit());
+                return (rel, vec![c, t, e]);
+            }
+        }
+        let (fanin0, fanin1) = self.nodes[n].fanin();
+        let rel = LitVvec::cnf_and(lit, &[fanin0.to_lit(), fanin1.to_lit()]);
+        (rel, vec![fanin0, fanin1])
+    }
+
+    /// Encodes only the cones of `roots`. With `compact`, the nodes of the
+    /// cones are renumbered densely in node order, keeping the constant as
+    /// variable 0. Returns the map from the variables of the cnf to node ids.
+    pub fn cnf_for(&self, roots: &[AigEdge], optimize: bool, compact: bool) -> (DagCnf, VarVMap) {
+        let mut rels = vec![None; self.num_nodes()];
+        let mut cone = vec![false; self.num_nodes()];
+        cone[0] = true;
+        let mut stack: Vec<usize> = roots.iter().map(|r| r.node_id()).collect();
+        while let Some(n) = stack.pop() {
+            if cone[n] {
+                continue;
+            }
+            cone[n] = true;
+            if self.nodes[n].is_and() {
+                let (rel, fanins) = self.node_rel(n, optimize);
+                stack.extend(fanins.iter().map(|e| e.node_id()));
+                rels[n] = Some(rel);
+            }
+        }
+        let mut var_map = vec![Var::new(0); self.num_nodes()];
+        let mut restore = VarVMap::new();
+        let mut ans = DagCnf::new();
+        restore.insert(Var::new(0), Var::new(0));
+        for n in self.nodes_range() {
+            if compact && !cone[n] {
+                continue;
+            }
+            var_map[n] = ans.new_var();
+            if cone[n] {
+                restore.insert(var_map[n], Var::new(n));
+            }
+        }
+        let map_lit = |l: Lit| var_map[usize::from(l.var())].lit().not_if(!l.polarity());
+        for (n, rel) in rels.iter().enumerate() {
+            if let Some(rel) = rel {
+                let rel: Vec<LitVec> = rel
+                    .iter()
+                    .map(|cls| cls.iter().map(|l| map_lit(*l)).collect())
+                    .collect();
+                ans.add_rel(var_map[n], &rel);
+            }
+        }
+        (ans, restore)
+    }
+
+    /// Plaisted-Greenbaum encoding. Only the cone of `roots` is encoded, and
+    /// each gate only gets the clauses of the direction needed to assert all
+    /// `roots` true. Variables are allocated for all nodes as in `cnf`.
+    pub fn cnf_pg(&self, optimize: bool, roots: &[AigEdge]) -> DagCnf {
+        const POS: u8 = 1;
+        const NEG: u8 = 2;
+        let mut pol = vec![0u8; self.num_nodes()];
+        for r in roots {
+            pol[r.node_id()] |= if r.compl() { NEG } else { POS };
+        }
+        let mut ans = DagCnf::new();
+        for node in self.nodes.iter().skip(1) {
+            assert_eq!(Var::new(node.node_id()), ans.new_var());
+        }
+        for i in self.nodes_range().rev() {
+            let p = pol[i];
+            if !self.nodes[i].is_and() || p == 0 {
+                continue;
+            }
+            let n = Var::new(i).lit();
+            let (rel, _) = self.node_rel(i, optimize);
+            let rel: Vec<LitVec> = rel
+                .iter()
+                .filter(|cls| {
+                    (p & POS != 0 && cls.contains(&!n)) || (p & NEG != 0 && cls.contains(&n))
+                })
+                .cloned()
+                .collect();
+            // A fanin literal in a kept clause needs the direction of its
+            // own relation implied by that literal.
+            for l in rel.iter().flatten().filter(|l| l.var() != n.var()) {
+                pol[usize::from(l.var())] |= if l.polarity() { POS } else { NEG };
+            }
+            ans.add_rel(n.var(), &rel);
+        }
+        ans
+    }
+}
+
+/// Incremental Tseitin encoder for a growing `Aig`. Remembers the encoded
+/// nodes, so each call only returns the clauses of nodes newly required by
+/// its roots. Node `n` is variable `n`, as in `Aig::cnf`, and the first
+/// call also returns the unit clause fixing variable 0 to false.
+#[derive(Debug, Clone, Default)]
+pub struct AigCnfEncoder {
+    optimize: bool,
+    encoded: Vec<bool>,
+}
+
+impl AigCnfEncoder {
+    pub fn new(optimize: bool) -> Self {
+        Self {
+            optimize,
+            encoded: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn is_encoded(&self, n: usize) -> bool {
+        self.encoded.get(n).copied().unwrap_or(false)
+    }
+
+    /// Encodes the not yet encoded and nodes in the cones of `roots`.
+    pub fn encode(&mut self, aig: &Aig, roots: &[AigEdge]) -> Vec<LitVec> {
+        self.encoded.resize(aig.num_nodes(), false);
+        let mut res = Vec::new();
+        if !self.encoded[0] {
+            self.encoded[0] = true;
+            res.push(LitVec::from([AigEdge::constant(true).to_lit()]));
+        }
+        let mut stack: Vec<usize> = roots.iter().map(|r| r.node_id()).collect();
+        while let Some(i) = stack.pop() {
+            if self.encoded[i] || !aig.nodes[i].is_and() {
+                continue;
+            }
+            self.encoded[i] = true;
+            let (rel, fanins) = aig.node_rel(i, self.optimize);
+            stack.extend(fanins.iter().map(|e| e.node_id()));
+            res.extend(rel.iter().cloned());
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cnf_pg() {
+        let mut aig = Aig::new();
+        let i0: AigEdge = aig.new_input().into();
+        let i1: AigEdge = aig.new_input().into();
+        let a = aig.new_and_node(i0, i1);
+        let b = aig.new_and_node(!a, i0);
+        aig.bads.push(b);
+        let cnf = aig.cnf_pg(false, &[b]);
+        // The constant, b -> !a, b -> i0, and !a needs the negative
+        // direction of a.
+        assert_eq!(cnf.clause().count(), 1 + 3);
+        let cnf = aig.cnf_pg(false, &[b, !b]);
+        assert_eq!(cnf.clause().count(), 1 + 6);
+    }
+
+    #[test]
+    fn test_cnf_encoder() {
+        let mut aig = Aig::new();
+        let i0: AigEdge = aig.new_input().into();
+        let i1: AigEdge = aig.new_input().into();
+        let a = aig.new_and_node(i0, i1);
+        let mut enc = AigCnfEncoder::new(false);
+        assert_eq!(enc.encode(&aig, &[a]).len(), 1 + 3);
+        let b = aig.new_and_node(!a, i0);
+        assert_eq!(enc.encode(&aig, &[b, a]).len(), 3);
+        assert!(enc.encode(&aig, &[b]).is_empty());
+    }
+
+    #[test]
+    fn test_cnf_for() {
+        let mut aig = Aig::new();
+        let i0: AigEdge = aig.new_input().into();
+        let i1: AigEdge = aig.new_input().into();
+        let i2: AigEdge = aig.new_input().into();
+        let a = aig.new_and_node(i0, i2);
+        aig.new_and_node(i1, i2);
+        let (cnf, restore) = aig.cnf_for(&[a], false, true);
+        assert_eq!(cnf.max_var(), Var::new(3));
+        assert_eq!(cnf.clause().count(), 1 + 3);
+        assert_eq!(restore[Var::new(2)], Var::new(i2.node_id()));
+        assert_eq!(restore[Var::new(3)], Var::new(a.node_id()));
+    }
 }
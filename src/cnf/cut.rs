@@ -0,0 +1,252 @@
+use crate::{Aig, AigNodeType};
+use logicrs::{DagCnf, LitVec, Var};
+
+/// Maximum number of leaves of a cut.
+const CUT_SIZE: usize = 4;
+/// Maximum number of non-trivial cuts kept per node.
+const CUT_LIMIT: usize = 8;
+const VAR_TT: [u16; CUT_SIZE] = [0xaaaa, 0xcccc, 0xf0f0, 0xff00];
+
+#[derive(Clone, Debug)]
+struct Cut {
+    leaves: Vec<usize>,
+    /// Truth table over `leaves`, leaf `j` being variable `j`.
+    tt: u16,
+}
+
+impl Cut {
+    fn trivial(n: usize) -> Self {
+        Self {
+            leaves: vec![n],
+            tt: VAR_TT[0],
+        }
+    }
+
+    fn dominates(&self, other: &Cut) -> bool {
+        self.leaves.iter().all(|l| other.leaves.contains(l))
+    }
+}
+
+/// Rewrites `tt` over `from` as a truth table over `to`, a superset of `from`.
+fn expand(tt: u16, from: &[usize], to: &[usize]) -> u16 {
+    let pos: Vec<usize> = from
+        .iter()
+        .map(|l| to.iter().position(|t| t == l).unwrap())
+        .collect();
+    let mut res = 0;
+    for m in 0..16 {
+        let mut fm = 0;
+        for (j, p) in pos.iter().enumerate() {
+            fm |= ((m >> p) & 1) << j;
+        }
+        res |= ((tt >> fm) & 1) << m;
+    }
+    res
+}
+
+fn merge_leaves(a: &[usize], b: &[usize]) -> Option<Vec<usize>> {
+    let mut res = Vec::with_capacity(CUT_SIZE);
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        let l = if j == b.len() || (i < a.len() && a[i] < b[j]) {
+            i += 1;
+            a[i - 1]
+        } else {
+            if i < a.len() && a[i] == b[j] {
+                i += 1;
+            }
+            j += 1;
+            b[j - 1]
+        };
+        res.push(l);
+        if res.len() > CUT_SIZE {
+            return None;
+        }
+    }
+    Some(res)
+}
+
+/// A cube over the first `CUT_SIZE` variables: variable `j` is in the cube if
+/// bit `j` of the mask is set, with the polarity given by bit `j` of the value.
+type Cube = (u8, u8);
+
+fn cube_tt((mask, val): Cube) -> u16 {
+    let mut res = !0;
+    for (j, v) in VAR_TT.iter().enumerate() {
+        if mask >> j & 1 == 1 {
+            res &= if val >> j & 1 == 1 { *v } else { !*v };
+        }
+    }
+    res
+}
+
+/// Greedy irredundant sum-of-products cover of `f` over `nvars` variables.
+fn sop_cover(f: u16, nvars: usize) -> Vec<Cube> {
+    let mut implicants = Vec::new();
+    for mask in 0..1u8 << nvars {
+        for val in 0..1u8 << nvars {
+            if val & !mask == 0 && cube_tt((mask, val)) & !f == 0 {
+                implicants.push((mask, val));
+            }
+        }
+    }
+    implicants.sort_by_key(|(mask, _)| mask.count_ones());
+    let mut res = Vec::new();
+    let mut uncovered = f;
+    while uncovered != 0 {
+        let best = *implicants
+            .iter()
+            .max_by_key(|c| (cube_tt(**c) & uncovered).count_ones())
+            .unwrap();
+        uncovered &= !cube_tt(best);
+        res.push(best);
+    }
+    res
+}
+
+fn cut_cost(cut: &Cut) -> usize {
+    let n = cut.leaves.len();
+    sop_cover(cut.tt, n).len() + sop_cover(!cut.tt, n).len()
+}
+
+impl Aig {
+    fn enumerate_cuts(&self) -> Vec<Vec<Cut>> {
+        let mut cuts: Vec<Vec<Cut>> = vec![Vec::new(); self.num_nodes()];
+        cuts[0].push(Cut {
+            leaves: Vec::new(),
+            tt: 0,
+        });
+        for node in self.nodes.iter().skip(1) {
+            let AigNodeType::And(fanin0, fanin1) = node.typ else {
+                cuts[node.id].push(Cut::trivial(node.id));
+                continue;
+            };
+            let mut res: Vec<Cut> = Vec::new();
+            for c0 in cuts[fanin0.node_id()].iter() {
+                for c1 in cuts[fanin1.node_id()].iter() {
+                    let Some(leaves) = merge_leaves(&c0.leaves, &c1.leaves) else {
+                        continue;
+                    };
+                    let tt0 = expand(c0.tt, &c0.leaves, &leaves);
+                    let tt1 = expand(c1.tt, &c1.leaves, &leaves);
+                    let tt0 = if fanin0.compl() { !tt0 } else { tt0 };
+                    let tt1 = if fanin1.compl() { !tt1 } else { tt1 };
+                    let cut = Cut {
+                        leaves,
+                        tt: tt0 & tt1,
+                    };
+                    if res.iter().any(|c| c.dominates(&cut)) {
+                        continue;
+                    }
+                    res.retain(|c| !cut.dominates(c));
+                    res.push(cut);
+                }
+            }
+            res.sort_by_key(|c| c.leaves.len());
+            res.truncate(CUT_LIMIT);
+            res.push(Cut::trivial(node.id));
+            cuts[node.id] = res;
+        }
+        cuts
+    }
+
+    /// Cut-based encoding. Enumerates cuts of up to four leaves, covers the
+    /// cone of the roots of `cnf` by cuts selected by area flow on the
+    /// number of clauses, and emits for each selected cut the clauses of
+    /// irredundant covers of its truth table and its complement. Only the
+    /// roots and the nodes used as cut leaves are constrained; variables of
+    /// nodes absorbed into cuts are left free.
+    pub fn cnf_cut(&self) -> DagCnf {
+        let cuts = self.enumerate_cuts();
+        let mut fanouts = vec![0usize; self.num_nodes()];
+        for node in self.ands_iter() {
+            let (fanin0, fanin1) = node.fanin();
+            fanouts[fanin0.node_id()] += 1;
+            fanouts[fanin1.node_id()] += 1;
+        }
+        let mut flow = vec![0.0f64; self.num_nodes()];
+        let mut best = vec![None; self.num_nodes()];
+        for node in self.ands_iter() {
+            let n = node.node_id();
+            let (b, f) = cuts[n]
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.leaves != [n])
+                .map(|(i, c)| {
+                    let leaf_flow: f64 = c.leaves.iter().map(|l| flow[*l]).sum();
+                    (i, cut_cost(c) as f64 + leaf_flow)
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            best[n] = Some(b);
+            flow[n] = f / fanouts[n].max(1) as f64;
+        }
+        let mut ans = DagCnf::new();
+        for node in self.nodes.iter().skip(1) {
+            assert_eq!(Var::new(node.node_id()), ans.new_var());
+        }
+        let mut mapped = vec![false; self.num_nodes()];
+        let mut stack: Vec<usize> = self.get_root_refs().into_iter().collect();
+        while let Some(n) = stack.pop() {
+            let Some(b) = best[n] else {
+                continue;
+            };
+            if mapped[n] {
+                continue;
+            }
+            mapped[n] = true;
+            let cut = &cuts[n][b];
+            stack.extend(cut.leaves.iter().copied());
+            let lit = Var::new(n).lit();
+            let nvars = cut.leaves.len();
+            let mut rel = Vec::new();
+            for (tt, out) in [(cut.tt, lit), (!cut.tt, !lit)] {
+                for (mask, val) in sop_cover(tt, nvars) {
+                    let mut cls = LitVec::new();
+                    cls.push(out);
+                    for (j, l) in cut.leaves.iter().enumerate() {
+                        if mask >> j & 1 == 1 {
+                            cls.push(Var::new(*l).lit().not_if(val >> j & 1 == 1));
+                        }
+                    }
+                    rel.push(cls);
+                }
+            }
+            ans.add_rel(lit.var(), &rel);
+        }
+        ans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AigEdge;
+
+    #[test]
+    fn test_cnf_cut() {
+        let mut aig = Aig::new();
+        let i0: AigEdge = aig.new_input().into();
+        let i1: AigEdge = aig.new_input().into();
+        let a0 = aig.new_and_node(i0, !i1);
+        let a1 = aig.new_and_node(!i0, i1);
+        let x = aig.new_or_node(a0, a1);
+        aig.bads.push(x);
+        let cnf = aig.cnf_cut();
+        assert_eq!(cnf.clause().count(), 1 + 4);
+        for m in 0..4 {
+            let mut value = vec![false; aig.num_nodes()];
+            value[i0.node_id()] = m & 1 == 1;
+            value[i1.node_id()] = m & 2 == 2;
+            let xv = value[i0.node_id()] ^ value[i1.node_id()];
+            for out in [false, true] {
+                value[x.node_id()] = xv ^ x.compl() ^ out;
+                let sat = cnf.clause().all(|cls| {
+                    cls.iter()
+                        .any(|l| value[usize::from(l.var())] == l.polarity())
+                });
+                assert_eq!(sat, !out);
+            }
+        }
+    }
+}
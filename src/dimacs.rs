@@ -0,0 +1,60 @@
+use crate::{Aig, AigEdge};
+use logicrs::LitVec;
+use std::io::{self, Write};
+
+impl Aig {
+    /// Writes a DIMACS query asserting all `roots`, with their cones encoded
+    /// by `cnf_for` as two-sided Tseitin relations. Node `n` becomes DIMACS
+    /// variable `n + 1`, variable 1 being the constant false. `c` lines map
+    /// variables back to nodes and symbols.
+    pub fn write_dimacs(&self, roots: &[AigEdge], mut w: impl Write) -> io::Result<()> {
+        let (cnf, _) = self.cnf_for(roots, false, false);
+        let mut clauses: Vec<LitVec> = cnf.clause().cloned().collect();
+        clauses.extend(roots.iter().map(|r| LitVec::from([r.to_lit()])));
+        let mut kind = vec![String::new(); self.num_nodes()];
+        kind[0] = " false".to_string();
+        for (k, i) in self.inputs.iter().enumerate() {
+            kind[*i] = format!(" input {k}");
+        }
+        for (k, l) in self.latchs.iter().enumerate() {
+            kind[l.input] = format!(" latch {k}");
+        }
+        for (n, kind) in kind.iter().enumerate() {
+            write!(w, "c {} node {n}{kind}", n + 1)?;
+            match self.get_symbol(n) {
+                Some(s) => writeln!(w, " {s}")?,
+                None => writeln!(w)?,
+            }
+        }
+        writeln!(w, "p cnf {} {}", self.num_nodes(), clauses.len())?;
+        for cls in clauses.iter() {
+            for l in cls.iter() {
+                let v = usize::from(l.var()) as isize + 1;
+                write!(w, "{} ", if l.polarity() { v } else { -v })?;
+            }
+            writeln!(w, "0")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_dimacs() {
+        let mut aig = Aig::new();
+        let i0 = aig.new_input();
+        let i1: AigEdge = aig.new_input().into();
+        aig.set_symbol(i0, "a");
+        let n = aig.new_and_node(i0.into(), !i1);
+        let mut dimacs = Vec::new();
+        aig.write_dimacs(&[!n], &mut dimacs).unwrap();
+        let dimacs = String::from_utf8(dimacs).unwrap();
+        assert!(dimacs.starts_with(
+            "c 1 node 0 false\nc 2 node 1 input 0 a\nc 3 node 2 input 1\nc 4 node 3\np cnf 4 5\n-1 0\n"
+        ));
+        assert!(dimacs.ends_with("\n-4 0\n"));
+    }
+}
@@ -1,17 +1,36 @@
 mod aiger;
+mod bitsim;
 pub mod cnf;
+mod dimacs;
 mod others;
+mod phase;
+mod sat;
 mod strash;
 mod ternary;
-
+mod trace;
+mod ts;
+mod unroll;
+mod validate;
+mod vcd;
+mod witness;
+
+pub use aiger::{AigerCompression, AigerError, AigerMode};
+pub use bitsim::*;
 use giputils::hash::GHashMap;
 use logicrs::Lit;
+pub use phase::*;
+pub use sat::*;
 use std::{
     mem::swap,
     ops::{Index, Not, Range},
     vec,
 };
 pub use ternary::*;
+pub use trace::*;
+pub use ts::*;
+pub use unroll::*;
+pub use validate::*;
+pub use witness::*;
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct AigEdge {
@@ -244,6 +263,17 @@ impl AigNode {
     }
 }
 
+/// Symbol table sections whose entries are named by their index rather than
+/// by a node id.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AigPropKind {
+    Output,
+    Bad,
+    Constraint,
+    Justice,
+    Fairness,
+}
+
 #[derive(Debug, Clone)]
 pub struct Aig {
     pub nodes: Vec<AigNode>,
@@ -255,6 +285,10 @@ pub struct Aig {
     pub justice: Vec<Vec<AigEdge>>,
     pub fairness: Vec<AigEdge>,
     pub symbols: GHashMap<usize, String>,
+    pub prop_symbols: GHashMap<(AigPropKind, usize), String>,
+    pub comments: Vec<String>,
+    /// Structural hash table used by `new_and_node` when enabled.
+    strash_table: Option<GHashMap<(AigEdge, AigEdge), usize>>,
 }
 
 impl Aig {
@@ -272,6 +306,9 @@ impl Aig {
             justice: Vec::new(),
             fairness: Vec::new(),
             symbols: Default::default(),
+            prop_symbols: Default::default(),
+            comments: Vec::new(),
+            strash_table: None,
         }
     }
 
@@ -313,10 +350,51 @@ impl Aig {
     pub fn trivial_new_and_node(&mut self, fanin0: AigEdge, fanin1: AigEdge) -> AigEdge {
         let nodeid = self.nodes.len();
         let and = AigNode::new_and(nodeid, fanin0, fanin1);
+        let (fanin0, fanin1) = and.fanin();
+        if self.strash_lookup(fanin0, fanin1).is_none()
+            && let Some(table) = &mut self.strash_table
+        {
+            table.insert((fanin0, fanin1), nodeid);
+        }
         self.nodes.push(and);
         nodeid.into()
     }
 
+    fn strash_lookup(&self, fanin0: AigEdge, fanin1: AigEdge) -> Option<AigEdge> {
+        let id = *self.strash_table.as_ref()?.get(&(fanin0, fanin1))?;
+        // entries may be stale after direct edits to `nodes`
+        let node = self.nodes.get(id)?;
+        (node.is_and() && node.fanin() == (fanin0, fanin1)).then(|| id.into())
+    }
+
+    /// Enables structural hashing in `new_and_node`, building the table from
+    /// the current and nodes.
+    pub fn enable_strash(&mut self) {
+        self.strash_table = Some(GHashMap::new());
+        self.rebuild_strash();
+    }
+
+    pub fn disable_strash(&mut self) {
+        self.strash_table = None;
+    }
+
+    #[inline]
+    pub fn is_strash_enabled(&self) -> bool {
+        self.strash_table.is_some()
+    }
+
+    /// Rebuilds the structural hash table after `nodes` has been edited
+    /// directly. Does nothing if structural hashing is disabled.
+    pub fn rebuild_strash(&mut self) {
+        let Some(table) = &mut self.strash_table else {
+            return;
+        };
+        table.clear();
+        for node in self.nodes.iter().filter(|n| n.is_and()) {
+            table.entry(node.fanin()).or_insert(node.id);
+        }
+    }
+
     #[inline]
     pub fn new_and_node(&mut self, mut fanin0: AigEdge, mut fanin1: AigEdge) -> AigEdge {
         if fanin0.node_id() > fanin1.node_id() {
@@ -338,6 +416,8 @@ impl Aig {
             fanin0
         } else if fanin0 == !fanin1 {
             AigEdge::constant(false)
+        } else if let Some(and) = self.strash_lookup(fanin0, fanin1) {
+            and
         } else {
             self.trivial_new_and_node(fanin0, fanin1)
         }
@@ -408,6 +488,16 @@ impl Aig {
     pub fn set_symbol(&mut self, id: usize, s: &str) {
         self.symbols.insert(id, s.to_string());
     }
+
+    #[inline]
+    pub fn get_prop_symbol(&self, kind: AigPropKind, idx: usize) -> Option<String> {
+        self.prop_symbols.get(&(kind, idx)).cloned()
+    }
+
+    #[inline]
+    pub fn set_prop_symbol(&mut self, kind: AigPropKind, idx: usize, s: &str) {
+        self.prop_symbols.insert((kind, idx), s.to_string());
+    }
 }
 
 impl Aig {
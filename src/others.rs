@@ -1,4 +1,4 @@
-use crate::{Aig, AigEdge, AigNodeType};
+use crate::{Aig, AigEdge, AigNodeType, AigPropKind};
 use giputils::hash::{GHashMap, GHashSet};
 use logicrs::{Var, VarVMap};
 use std::mem::take;
@@ -122,6 +122,9 @@ impl Aig {
                 bads,
                 constraints,
                 symbols,
+                prop_symbols: self.prop_symbols.clone(),
+                comments: self.comments.clone(),
+                strash_table: None,
                 justice,
                 fairness,
             },
@@ -141,8 +144,8 @@ impl Aig {
                 continue;
             }
             if from.nodes[i].is_and() {
-                let fanin0 = self.nodes[i].fanin0();
-                let fanin1 = self.nodes[i].fanin1();
+                let fanin0 = from.nodes[i].fanin0();
+                let fanin1 = from.nodes[i].fanin1();
                 let fanin0 = next_map[&fanin0.node_id()].not_if(fanin0.compl());
                 let fanin1 = next_map[&fanin1.node_id()].not_if(fanin1.compl());
                 let next = self.new_and_node(fanin0, fanin1);
@@ -188,6 +191,20 @@ impl Aig {
         let map = |x: usize| {
             if x == 0 { x } else { x + offset }
         };
+        for ((kind, i), s) in other.prop_symbols.iter() {
+            let i = i + match kind {
+                AigPropKind::Output => self.outputs.len(),
+                AigPropKind::Bad => self.bads.len(),
+                AigPropKind::Constraint => self.constraints.len(),
+                AigPropKind::Justice => self.justice.len(),
+                AigPropKind::Fairness => self.fairness.len(),
+            };
+            self.prop_symbols.insert((*kind, i), s.clone());
+        }
+        for (id, s) in other.symbols.iter() {
+            self.symbols.insert(map(*id), s.clone());
+        }
+        self.comments.extend(other.comments.iter().cloned());
         for i in 1..other.num_nodes() {
             let n = other.nodes[i].map(&map);
             self.nodes.push(n);
@@ -268,6 +285,8 @@ impl Aig {
             .iter()
             .map(|(id, s)| (encode_map[id], s.clone()))
             .collect();
+        res.prop_symbols = self.prop_symbols.clone();
+        res.comments = self.comments.clone();
         assert!(res.nodes.len() == self.nodes.len());
         res
     }
@@ -290,6 +309,8 @@ impl Aig {
 
     pub fn compress_property(&mut self) -> Vec<AigEdge> {
         let b = take(&mut self.bads);
+        self.prop_symbols
+            .retain(|(kind, _), _| *kind != AigPropKind::Bad);
         let p = self.new_ors_node(b.clone());
         self.bads.push(p);
         b
@@ -319,3 +340,39 @@ impl Aig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use logicrs::Lbool;
+
+    #[test]
+    fn test_unroll() {
+        let mut from = Aig::new();
+        let i: AigEdge = from.new_input().into();
+        let l = from.new_leaf_node();
+        let n = from.new_and_node(i, l.into());
+        let b = from.new_and_node(i, !AigEdge::from(l));
+        from.add_latch(l, n, None);
+        from.bads.push(b);
+        // Same leaves, but different and nodes at the ids of `from`.
+        let mut aig = Aig::new();
+        let i0: AigEdge = aig.new_input().into();
+        let l0 = aig.new_leaf_node();
+        aig.new_and_node(!i0, !AigEdge::from(l0));
+        let n0 = aig.new_and_node(i0, l0.into());
+        aig.add_latch(l0, n0, None);
+        aig.unroll(&from);
+        assert_eq!(aig.inputs.len(), 2);
+        for m in 0..8 {
+            let v = |k: usize| Lbool::from(m >> k & 1 == 1);
+            let value = aig.ternary_simulate(&[v(0), v(1)], &[v(2)]);
+            let bad = aig.bads[0];
+            let expect = (m & 2 == 2) && !(m & 1 == 1 && m & 4 == 4);
+            assert_eq!(
+                value[bad.node_id()].not_if(bad.compl()),
+                Lbool::from(expect)
+            );
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{Aig, AigEdge, AigLatch, AigNodeType, AigWitness};
+use logicrs::Lbool;
+
+/// The result of `Aig::phase_abstract`. One cycle of `aig` covers `period`
+/// consecutive cycles of the original aig.
+#[derive(Debug, Clone)]
+pub struct AigPhaseAbstract {
+    pub aig: Aig,
+    pub period: usize,
+    /// `phases[j][l]` is the value of original latch `l` in the `j`-th cycle
+    /// of every period, or `None` if the latch is not periodic.
+    pub phases: Vec<Vec<Option<bool>>>,
+    /// The original latch index of each latch of `aig`. A trailing latch not
+    /// listed here records that the constraints held in all previous cycles.
+    pub latchs: Vec<usize>,
+    /// `inputs[j][i]` is the index in `aig.inputs` of original input `i` in
+    /// the `j`-th cycle of every period.
+    pub inputs: Vec<Vec<usize>>,
+}
+
+impl AigPhaseAbstract {
+    /// Translates a witness of the abstracted aig into one of the original
+    /// aig. Each input frame is split into `period` frames.
+    pub fn lift_witness(&self, w: &AigWitness) -> AigWitness {
+        let mut init: Vec<Lbool> = self.phases[0]
+            .iter()
+            .map(|p| p.map_or(Lbool::NONE, Lbool::from))
+            .collect();
+        for (k, l) in self.latchs.iter().enumerate() {
+            init[*l] = w.init[k];
+        }
+        let mut inputs = Vec::new();
+        for frame in w.inputs.iter() {
+            for phase in self.inputs.iter() {
+                inputs.push(phase.iter().map(|k| frame[*k]).collect());
+            }
+        }
+        AigWitness {
+            props: w.props.clone(),
+            init,
+            inputs,
+        }
+    }
+}
+
+impl Aig {
+    /// Phase abstraction. Finds the ternary cycle reached from the initial
+    /// state within `max_steps` steps, and if some latches take known,
+    /// non-constant values repeating with its period from the initial state,
+    /// folds `period` consecutive cycles into one with these latches replaced
+    /// by their per-phase values. A bad is reached in the result exactly when
+    /// it is reached in one of the folded cycles with all constraints holding
+    /// up to that cycle.
+    pub fn phase_abstract(&self, max_steps: usize) -> Option<AigPhaseAbstract> {
+        let cycle = self.ternary_cycle(max_steps)?;
+        let period = cycle.period;
+        if period < 2 {
+            return None;
+        }
+        let at = |t: usize| {
+            let t = if t < cycle.states.len() {
+                t
+            } else {
+                cycle.prefix + (t - cycle.prefix) % period
+            };
+            &cycle.states[t]
+        };
+        let periodic: Vec<bool> = (0..self.latchs.len())
+            .map(|l| {
+                (0..cycle.states.len())
+                    .all(|t| at(t)[l] != Lbool::NONE && at(t)[l] == at(t + period)[l])
+                    && (1..period).any(|t| at(t)[l] != at(0)[l])
+            })
+            .collect();
+        if !periodic.iter().any(|p| *p) {
+            return None;
+        }
+        let phases: Vec<Vec<Option<bool>>> = (0..period)
+            .map(|t| {
+                at(t)
+                    .iter()
+                    .zip(periodic.iter())
+                    .map(|(v, p)| p.then(|| *v == Lbool::TRUE))
+                    .collect()
+            })
+            .collect();
+        let mut res = Aig::new();
+        res.enable_strash();
+        let latchs: Vec<usize> = (0..self.latchs.len()).filter(|l| !periodic[*l]).collect();
+        let leaves: Vec<usize> = latchs.iter().map(|_| res.new_leaf_node()).collect();
+        let mut latch_idx = vec![None; self.num_nodes()];
+        for (i, l) in self.latchs.iter().enumerate() {
+            latch_idx[l.input] = Some(i);
+        }
+        let mut input_idx = vec![None; self.num_nodes()];
+        for (i, n) in self.inputs.iter().enumerate() {
+            input_idx[*n] = Some(i);
+        }
+        let mut inputs = Vec::with_capacity(period);
+        let mut state = vec![AigEdge::constant(false); self.latchs.len()];
+        for (l, leaf) in latchs.iter().zip(leaves.iter()) {
+            state[*l] = (*leaf).into();
+        }
+        let mut first = Vec::new();
+        let mut bads = vec![AigEdge::constant(false); self.bads.len()];
+        let mut justice: Vec<Vec<AigEdge>> = self
+            .justice
+            .iter()
+            .map(|j| vec![AigEdge::constant(false); j.len()])
+            .collect();
+        let mut fairness = vec![AigEdge::constant(false); self.fairness.len()];
+        let mut valid = AigEdge::constant(true);
+        for phase in phases.iter() {
+            for (l, p) in phase.iter().enumerate() {
+                if let Some(p) = p {
+                    state[l] = AigEdge::constant(*p);
+                }
+            }
+            let mut map = vec![AigEdge::constant(false); self.num_nodes()];
+            let mut phase_inputs = vec![0; self.inputs.len()];
+            for node in self.nodes.iter().skip(1) {
+                map[node.id] = match node.typ {
+                    AigNodeType::And(fanin0, fanin1) => {
+                        let fanin0 = map[fanin0.node_id()].not_if(fanin0.compl());
+                        let fanin1 = map[fanin1.node_id()].not_if(fanin1.compl());
+                        res.new_and_node(fanin0, fanin1)
+                    }
+                    _ => match latch_idx[node.id] {
+                        Some(l) => state[l],
+                        None => {
+                            if let Some(i) = input_idx[node.id] {
+                                phase_inputs[i] = res.inputs.len();
+                            }
+                            res.new_input().into()
+                        }
+                    },
+                };
+            }
+            inputs.push(phase_inputs);
+            let edge_map = |e: &AigEdge| map[e.node_id()].not_if(e.compl());
+            let constraints: Vec<AigEdge> = self.constraints.iter().map(edge_map).collect();
+            valid = res.new_ands_node(constraints.into_iter().chain([valid]));
+            for (b, e) in bads.iter_mut().zip(self.bads.iter()) {
+                let bad = res.new_and_node(edge_map(e), valid);
+                *b = res.new_or_node(*b, bad);
+            }
+            for (js, j) in justice.iter_mut().zip(self.justice.iter()) {
+                for (s, e) in js.iter_mut().zip(j.iter()) {
+                    *s = res.new_or_node(*s, edge_map(e));
+                }
+            }
+            for (f, e) in fairness.iter_mut().zip(self.fairness.iter()) {
+                *f = res.new_or_node(*f, edge_map(e));
+            }
+            res.outputs.extend(self.outputs.iter().map(edge_map));
+            state = self.latchs.iter().map(|l| edge_map(&l.next)).collect();
+            if first.is_empty() {
+                first = map;
+            }
+        }
+        res.disable_strash();
+        for (l, leaf) in latchs.iter().zip(leaves.iter()) {
+            let latch = &self.latchs[*l];
+            let init = latch.init.map(|i| first[i.node_id()].not_if(i.compl()));
+            res.latchs.push(AigLatch::new(*leaf, state[*l], init));
+            if let Some(s) = self.get_symbol(latch.input) {
+                res.set_symbol(*leaf, &s);
+            }
+        }
+        if !self.constraints.is_empty() {
+            let v = res.new_latch(valid, Some(AigEdge::constant(true)));
+            res.constraints.push(v.into());
+        }
+        res.bads = bads;
+        res.justice = justice;
+        res.fairness = fairness;
+        Some(AigPhaseAbstract {
+            aig: res,
+            period,
+            phases,
+            latchs,
+            inputs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_abstract() {
+        let mut aig = Aig::new();
+        let i: AigEdge = aig.new_input().into();
+        let clk = aig.new_leaf_node();
+        aig.add_latch(clk, !AigEdge::from(clk), Some(AigEdge::constant(false)));
+        let d = aig.new_leaf_node();
+        let next = aig.new_and_node(clk.into(), i);
+        aig.add_latch(d, next, Some(AigEdge::constant(false)));
+        aig.bads.push(d.into());
+        let pa = aig.phase_abstract(10).unwrap();
+        assert_eq!(pa.period, 2);
+        assert_eq!(pa.latchs, vec![1]);
+        assert_eq!(pa.aig.inputs.len(), 2);
+        let w = AigWitness {
+            props: vec![(crate::AigPropKind::Bad, 0)],
+            init: vec![Lbool::FALSE],
+            inputs: vec![
+                vec![Lbool::NONE, Lbool::TRUE],
+                vec![Lbool::NONE, Lbool::NONE],
+            ],
+        };
+        assert!(pa.aig.check_witness(&w).is_ok());
+        let w = pa.lift_witness(&w);
+        assert_eq!(w.inputs.len(), 4);
+        assert_eq!(aig.check_witness(&w), Ok((0, 2)));
+    }
+    #[test]
+    fn test_phase_abstract_input_order() {
+        let mut aig = Aig::new();
+        let a = aig.new_leaf_node();
+        let b = aig.new_leaf_node();
+        aig.add_input(b);
+        aig.add_input(a);
+        let clk = aig.new_leaf_node();
+        aig.add_latch(clk, !AigEdge::from(clk), Some(AigEdge::constant(false)));
+        let d = aig.new_leaf_node();
+        let en = aig.new_and_node(clk.into(), a.into());
+        let next = aig.new_and_node(en, !AigEdge::from(b));
+        aig.add_latch(d, next, Some(AigEdge::constant(false)));
+        aig.bads.push(d.into());
+        let pa = aig.phase_abstract(10).unwrap();
+        assert_eq!(pa.aig.inputs.len(), 4);
+        let mut frame = vec![Lbool::NONE; 4];
+        frame[pa.inputs[1][0]] = Lbool::FALSE;
+        frame[pa.inputs[1][1]] = Lbool::TRUE;
+        let w = AigWitness {
+            props: vec![(crate::AigPropKind::Bad, 0)],
+            init: vec![Lbool::FALSE],
+            inputs: vec![frame, vec![Lbool::NONE; 4]],
+        };
+        assert!(pa.aig.check_witness(&w).is_ok());
+        let w = pa.lift_witness(&w);
+        assert_eq!(w.inputs[1], vec![Lbool::FALSE, Lbool::TRUE]);
+        assert_eq!(aig.check_witness(&w), Ok((0, 2)));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{Aig, AigEdge};
+use logicrs::{Lbool, Lit, LitVec, Var};
+
+/// The interface the SAT-based algorithms of this crate need from a solver.
+/// Variables are created on demand by the clauses that mention them, and
+/// clauses may be added between calls to `solve`.
+pub trait AigSatSolver {
+    fn add_clause(&mut self, clause: &[Lit]);
+
+    /// Solves under `assumps`. Returns whether the clauses and the
+    /// assumptions are satisfiable.
+    fn solve(&mut self, assumps: &[Lit]) -> bool;
+
+    /// Value of `lit` in the model of the last satisfiable call.
+    fn sat_value(&self, lit: Lit) -> Option<bool>;
+
+    fn add_cnf<'a>(&mut self, cnf: impl IntoIterator<Item = &'a LitVec>) {
+        for cls in cnf {
+            self.add_clause(cls);
+        }
+    }
+}
+
+/// Solvers that can explain unsatisfiable calls.
+pub trait AigSatSolverCore: AigSatSolver {
+    /// Whether the assumption `lit` is part of the unsatisfiable core of the
+    /// last unsatisfiable call.
+    fn unsat_has(&self, lit: Lit) -> bool;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reason {
+    Assump,
+    Decision,
+    Flipped,
+    Implied,
+}
+
+/// A plain DPLL solver, meant as a reference for tests and small queries.
+/// Its unsat core is the set of all assumptions of the last call.
+#[derive(Debug, Clone, Default)]
+pub struct SimpleSatSolver {
+    clauses: Vec<LitVec>,
+    value: Vec<Lbool>,
+    trail: Vec<(Lit, Reason)>,
+    assumps: Vec<Lit>,
+}
+
+impl SimpleSatSolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    fn lit_value(&self, l: Lit) -> Lbool {
+        self.value[usize::from(l.var())].not_if(!l.polarity())
+    }
+
+    fn assign(&mut self, l: Lit, reason: Reason) {
+        self.value[usize::from(l.var())] = Lbool::from(l.polarity());
+        self.trail.push((l, reason));
+    }
+
+    /// Unit propagation to a fixpoint. Returns false on a conflict.
+    fn propagate(&mut self) -> bool {
+        loop {
+            let mut changed = false;
+            for i in 0..self.clauses.len() {
+                let mut unit = None;
+                let mut free = 0;
+                let mut sat = false;
+                for l in self.clauses[i].iter() {
+                    match self.lit_value(*l) {
+                        Lbool::TRUE => {
+                            sat = true;
+                            break;
+                        }
+                        Lbool::FALSE => (),
+                        _ => {
+                            free += 1;
+                            unit = Some(*l);
+                        }
+                    }
+                }
+                if sat {
+                    continue;
+                }
+                match (free, unit) {
+                    (0, _) => return false,
+                    (1, Some(l)) => {
+                        self.assign(l, Reason::Implied);
+                        changed = true;
+                    }
+                    _ => (),
+                }
+            }
+            if !changed {
+                return true;
+            }
+        }
+    }
+
+    /// Undoes the trail up to the last open decision and flips it. Returns
+    /// false if there is none left.
+    fn backtrack(&mut self) -> bool {
+        while let Some((l, reason)) = self.trail.pop() {
+            self.value[usize::from(l.var())] = Lbool::NONE;
+            match reason {
+                Reason::Decision => {
+                    self.assign(!l, Reason::Flipped);
+                    return true;
+                }
+                Reason::Assump => return false,
+                _ => (),
+            }
+        }
+        false
+    }
+}
+
+impl AigSatSolver for SimpleSatSolver {
+    fn add_clause(&mut self, clause: &[Lit]) {
+        for l in clause {
+            let v = usize::from(l.var());
+            if v >= self.value.len() {
+                self.value.resize(v + 1, Lbool::NONE);
+            }
+        }
+        self.clauses.push(LitVec::from(clause));
+    }
+
+    fn solve(&mut self, assumps: &[Lit]) -> bool {
+        self.trail.clear();
+        self.value.fill(Lbool::NONE);
+        self.assumps = assumps.to_vec();
+        for a in assumps {
+            let v = usize::from(a.var());
+            if v >= self.value.len() {
+                self.value.resize(v + 1, Lbool::NONE);
+            }
+            match self.lit_value(*a) {
+                Lbool::TRUE => (),
+                Lbool::FALSE => return false,
+                _ => self.assign(*a, Reason::Assump),
+            }
+            if !self.propagate() {
+                return false;
+            }
+        }
+        loop {
+            if !self.propagate() {
+                if !self.backtrack() {
+                    return false;
+                }
+                continue;
+            }
+            let Some(v) = self.value.iter().position(|v| *v == Lbool::NONE) else {
+                return true;
+            };
+            self.assign(Var::new(v).lit(), Reason::Decision);
+        }
+    }
+
+    fn sat_value(&self, lit: Lit) -> Option<bool> {
+        match self
+            .value
+            .get(usize::from(lit.var()))?
+            .not_if(!lit.polarity())
+        {
+            Lbool::TRUE => Some(true),
+            Lbool::FALSE => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl AigSatSolverCore for SimpleSatSolver {
+    fn unsat_has(&self, lit: Lit) -> bool {
+        self.assumps.contains(&lit)
+    }
+}
+
+impl Aig {
+    /// Searches for input and latch values making all `roots` true at once,
+    /// treating latches as free. Returns their values in the order of
+    /// `inputs` followed by `latchs`.
+    pub fn sat_check<S: AigSatSolver>(
+        &self,
+        solver: &mut S,
+        roots: &[AigEdge],
+    ) -> Option<Vec<bool>> {
+        let (cnf, _) = self.cnf_for(roots, true, false);
+        solver.add_clause(&[AigEdge::constant(true).to_lit()]);
+        solver.add_cnf(cnf.clause());
+        let assumps: Vec<Lit> = roots.iter().map(|r| r.to_lit()).collect();
+        if !solver.solve(&assumps) {
+            return None;
+        }
+        let leaves = self
+            .inputs
+            .iter()
+            .copied()
+            .chain(self.latchs.iter().map(|l| l.input));
+        Some(
+            leaves
+                .map(|n| solver.sat_value(Var::new(n).lit()).unwrap_or(false))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sat_check() {
+        let mut aig = Aig::new();
+        let i0: AigEdge = aig.new_input().into();
+        let i1: AigEdge = aig.new_input().into();
+        let x0 = aig.new_and_node(i0, !i1);
+        let x1 = aig.new_and_node(!i0, i1);
+        let x = aig.new_or_node(x0, x1);
+        let mut solver = SimpleSatSolver::new();
+        let model = aig.sat_check(&mut solver, &[x, i1]).unwrap();
+        assert_eq!(model, vec![false, true]);
+        let a = aig.new_and_node(i0, i1);
+        let mut solver = SimpleSatSolver::new();
+        assert!(aig.sat_check(&mut solver, &[x, a]).is_none());
+        assert!(solver.unsat_has(x.to_lit()));
+    }
+}
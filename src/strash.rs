@@ -1,36 +1,93 @@
-use crate::{Aig, AigEdge};
+use crate::{Aig, AigEdge, AigLatch, AigNodeType};
 use giputils::hash::GHashMap;
-use std::mem::swap;
 
 impl Aig {
-    pub fn strash(&self) -> Self {
-        let mut map = GHashMap::new();
-        let mut strash_map = GHashMap::new();
-        for node in self.nodes.iter() {
-            if node.is_and() {
-                let mut fanin0 = node.fanin0();
-                if let Some(eq) = strash_map.get(&fanin0.node_id()) {
-                    fanin0 = AigEdge::new(*eq, fanin0.complement);
-                }
-                let mut fanin1 = node.fanin1();
-                if let Some(eq) = strash_map.get(&fanin1.node_id()) {
-                    fanin1 = AigEdge::new(*eq, fanin1.complement);
-                }
-                if fanin0.node_id() > fanin1.node_id() {
-                    swap(&mut fanin0, &mut fanin1);
-                }
-                match map.get(&(fanin0, fanin1)) {
-                    Some(eq) => {
-                        strash_map.insert(node.node_id(), *eq);
-                    }
-                    None => {
-                        map.insert((fanin0, fanin1), node.id);
-                    }
+    /// Structural hashing. Merges structurally identical and nodes and folds
+    /// constant and trivial ones. Returns the new aig together with the edge
+    /// that each node of `self` is mapped to.
+    pub fn strash(&self) -> (Self, Vec<AigEdge>) {
+        self.strash_subst(&GHashMap::new())
+    }
+
+    /// Structural hashing after replacing the leaf nodes in `subst` by the
+    /// given constants. Substituted inputs and latches are removed.
+    pub(crate) fn strash_subst(&self, subst: &GHashMap<usize, bool>) -> (Self, Vec<AigEdge>) {
+        let mut res = Self::new();
+        res.enable_strash();
+        let mut map = vec![AigEdge::constant(false); self.num_nodes()];
+        for node in self.nodes.iter().skip(1) {
+            map[node.id] = match node.typ {
+                AigNodeType::And(fanin0, fanin1) => {
+                    let fanin0 = map[fanin0.id].not_if(fanin0.compl());
+                    let fanin1 = map[fanin1.id].not_if(fanin1.compl());
+                    res.new_and_node(fanin0, fanin1)
                 }
+                _ => match subst.get(&node.id) {
+                    Some(c) => AigEdge::constant(*c),
+                    None => res.new_leaf_node().into(),
+                },
+            };
+        }
+        res.disable_strash();
+        let edge_map = |e: AigEdge| map[e.node_id()].not_if(e.compl());
+        res.inputs = self
+            .inputs
+            .iter()
+            .filter(|i| !subst.contains_key(i))
+            .map(|i| map[*i].node_id())
+            .collect();
+        res.latchs = self
+            .latchs
+            .iter()
+            .filter(|l| !subst.contains_key(&l.input))
+            .map(|l| {
+                AigLatch::new(
+                    map[l.input].node_id(),
+                    edge_map(l.next),
+                    l.init.map(edge_map),
+                )
+            })
+            .collect();
+        res.outputs = self.outputs.iter().map(|e| edge_map(*e)).collect();
+        res.bads = self.bads.iter().map(|e| edge_map(*e)).collect();
+        res.constraints = self.constraints.iter().map(|e| edge_map(*e)).collect();
+        res.justice = self
+            .justice
+            .iter()
+            .map(|j| j.iter().map(|e| edge_map(*e)).collect())
+            .collect();
+        res.fairness = self.fairness.iter().map(|e| edge_map(*e)).collect();
+        for (id, s) in self.symbols.iter() {
+            let e = map[*id];
+            if !e.compl() && !e.is_const() {
+                res.symbols.insert(e.node_id(), s.clone());
             }
         }
-        println!("{:?}", strash_map.len());
-        println!("{:?}", map.len());
-        todo!()
+        res.prop_symbols = self.prop_symbols.clone();
+        res.comments = self.comments.clone();
+        (res, map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strash() {
+        let mut aig = Aig::new();
+        let i0: AigEdge = aig.new_input().into();
+        let i1: AigEdge = aig.new_input().into();
+        let a0 = aig.trivial_new_and_node(i0, i1);
+        let a1 = aig.trivial_new_and_node(i1, i0);
+        let a2 = aig.trivial_new_and_node(a0, !a1);
+        let a3 = aig.trivial_new_and_node(a1, AigEdge::constant(true));
+        aig.bads.push(a2);
+        aig.outputs.push(a3);
+        let (res, map) = aig.strash();
+        assert_eq!(res.num_nodes(), 4);
+        assert_eq!(map[a0.node_id()], map[a1.node_id()]);
+        assert!(res.bads[0].is_constant(false));
+        assert_eq!(res.outputs[0], map[a0.node_id()]);
     }
 }
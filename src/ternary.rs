@@ -1,4 +1,5 @@
 use crate::{Aig, AigEdge};
+use giputils::hash::GHashMap;
 use logicrs::Lbool;
 
 impl Aig {
@@ -24,6 +25,70 @@ impl Aig {
         }
         ans
     }
+
+    /// Over-approximates the reachable latch values by ternary simulation from
+    /// the initial state with all inputs X, turning a latch X as soon as it
+    /// changes. Returns the value of each latch that is constant in every
+    /// reachable state.
+    pub fn ternary_const_latchs(&self) -> Vec<Option<bool>> {
+        let input = vec![Lbool::NONE; self.inputs.len()];
+        let mut state = self.init_state(None, &input);
+        loop {
+            let value = self.ternary_simulate(&input, &state);
+            let mut fixpoint = true;
+            for (s, l) in state.iter_mut().zip(self.latchs.iter()) {
+                let next = value[l.next.node_id()].not_if(l.next.compl());
+                if *s != Lbool::NONE && *s != next {
+                    *s = Lbool::NONE;
+                    fixpoint = false;
+                }
+            }
+            if fixpoint {
+                break;
+            }
+        }
+        state
+            .iter()
+            .map(|s| match *s {
+                Lbool::TRUE => Some(true),
+                Lbool::FALSE => Some(false),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Ternary cycle reached from the initial state with all inputs X.
+    pub fn ternary_cycle(&self, max_steps: usize) -> Option<TernaryCycle> {
+        let input = vec![Lbool::NONE; self.inputs.len()];
+        TernarySimulate::new(self, self.init_state(None, &input)).find_cycle(max_steps)
+    }
+
+    /// Replaces the latches found constant by `ternary_const_latchs` with their
+    /// values and removes them. Returns the reduced aig together with the
+    /// removed latches, as indices into `self.latchs`, and their values. The
+    /// remaining latches keep their relative order.
+    pub fn const_latch_reduce(&self) -> (Self, Vec<(usize, bool)>) {
+        let consts: Vec<(usize, bool)> = self
+            .ternary_const_latchs()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, c)| c.map(|c| (i, c)))
+            .collect();
+        let mut subst = GHashMap::new();
+        for (i, c) in consts.iter() {
+            subst.insert(self.latchs[*i].input, *c);
+        }
+        let (res, _) = self.strash_subst(&subst);
+        (res, consts)
+    }
+}
+
+/// A lasso of ternary latch states: `states[prefix..]` repeats forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TernaryCycle {
+    pub states: Vec<Vec<Lbool>>,
+    pub prefix: usize,
+    pub period: usize,
 }
 
 pub struct TernarySimulate<'a> {
@@ -50,7 +115,79 @@ impl<'a> TernarySimulate<'a> {
         }
     }
 
+    pub fn state(&self) -> &[Lbool] {
+        &self.state
+    }
+
     pub fn value(&self, e: AigEdge) -> Lbool {
         self.value[e.node_id()].not_if(e.compl())
     }
+
+    /// Simulates with all inputs X from the current state until a state
+    /// repeats, giving up after `max_steps` steps. Every reachable concrete
+    /// state is covered by some state of the returned cycle when starting from
+    /// the initial state.
+    pub fn find_cycle(&mut self, max_steps: usize) -> Option<TernaryCycle> {
+        let key = |state: &[Lbool]| -> Vec<u8> {
+            state
+                .iter()
+                .map(|v| match *v {
+                    Lbool::FALSE => 0,
+                    Lbool::TRUE => 1,
+                    _ => 2,
+                })
+                .collect()
+        };
+        let input = vec![Lbool::NONE; self.aig.inputs.len()];
+        let mut seen = GHashMap::new();
+        let mut states = Vec::new();
+        for step in 0..=max_steps {
+            if let Some(prefix) = seen.insert(key(&self.state), step) {
+                return Some(TernaryCycle {
+                    states,
+                    prefix,
+                    period: step - prefix,
+                });
+            }
+            states.push(self.state.clone());
+            self.simulate(input.clone());
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_const_latch_reduce() {
+        let mut aig = Aig::new();
+        let i: AigEdge = aig.new_input().into();
+        let l0 = aig.new_leaf_node();
+        let l1 = aig.new_leaf_node();
+        let n0 = aig.new_and_node(l0.into(), i);
+        aig.add_latch(l0, n0, Some(AigEdge::constant(false)));
+        aig.add_latch(l1, i, Some(AigEdge::constant(false)));
+        let b = aig.new_and_node(l0.into(), l1.into());
+        aig.bads.push(b);
+        assert_eq!(aig.ternary_const_latchs(), vec![Some(false), None]);
+        let (res, consts) = aig.const_latch_reduce();
+        assert_eq!(consts, vec![(0, false)]);
+        assert_eq!(res.latchs.len(), 1);
+        assert!(res.bads[0].is_constant(false));
+    }
+
+    #[test]
+    fn test_ternary_cycle() {
+        let mut aig = Aig::new();
+        let l0 = aig.new_leaf_node();
+        let l1 = aig.new_leaf_node();
+        aig.add_latch(l0, AigEdge::constant(true), Some(AigEdge::constant(false)));
+        aig.add_latch(l1, !AigEdge::from(l1), Some(AigEdge::constant(false)));
+        let c = aig.ternary_cycle(10).unwrap();
+        assert_eq!((c.prefix, c.period), (1, 2));
+        assert_eq!(c.states[1], vec![Lbool::TRUE, Lbool::TRUE]);
+        assert_eq!(c.states[2], vec![Lbool::TRUE, Lbool::FALSE]);
+    }
 }
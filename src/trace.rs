@@ -0,0 +1,154 @@
+use crate::{Aig, TernarySimulate};
+use logicrs::Lbool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AigSimEvent {
+    BadReached { cycle: usize, bad: usize },
+    ConstraintViolated { cycle: usize, constraint: usize },
+}
+
+/// Per-cycle values recorded by `Aig::simulate_trace`.
+#[derive(Debug, Clone, Default)]
+pub struct AigTrace {
+    pub inputs: Vec<Vec<Lbool>>,
+    pub latchs: Vec<Vec<Lbool>>,
+    pub outputs: Vec<Vec<Lbool>>,
+    pub bads: Vec<Vec<Lbool>>,
+    pub constraints: Vec<Vec<Lbool>>,
+}
+
+impl AigTrace {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// The first cycle in which a constraint is definitely violated or a bad
+    /// is definitely reached. Violations take precedence within a cycle.
+    pub fn first_event(&self) -> Option<AigSimEvent> {
+        for cycle in 0..self.len() {
+            if let Some(constraint) = self.constraints[cycle]
+                .iter()
+                .position(|v| *v == Lbool::FALSE)
+            {
+                return Some(AigSimEvent::ConstraintViolated { cycle, constraint });
+            }
+            if let Some(bad) = self.bads[cycle].iter().position(|v| *v == Lbool::TRUE) {
+                return Some(AigSimEvent::BadReached { cycle, bad });
+            }
+        }
+        None
+    }
+}
+
+impl Aig {
+    /// Initial latch values. Latches with a constant init take it, latches
+    /// without one take their value in `free` (X if not given), and
+    /// gate-defined inits are evaluated under `input` and the other initial
+    /// values.
+    pub fn init_state(&self, free: Option<&[Lbool]>, input: &[Lbool]) -> Vec<Lbool> {
+        let mut state: Vec<Lbool> = self
+            .latchs
+            .iter()
+            .enumerate()
+            .map(|(i, l)| match l.init {
+                Some(init) if init.is_const() => Lbool::from(init.to_constant()),
+                None => free.map_or(Lbool::NONE, |f| f[i]),
+                Some(_) => Lbool::NONE,
+            })
+            .collect();
+        if self
+            .latchs
+            .iter()
+            .any(|l| l.init.is_some_and(|i| !i.is_const()))
+        {
+            let value = self.ternary_simulate(input, &state);
+            for (s, l) in state.iter_mut().zip(self.latchs.iter()) {
+                if let Some(init) = l.init
+                    && !init.is_const()
+                {
+                    *s = value[init.node_id()].not_if(init.compl());
+                }
+            }
+        }
+        state
+    }
+
+    /// Simulates `inputs` cycle by cycle from the initial state and records
+    /// the values of inputs, latches, outputs, bads and constraints.
+    pub fn simulate_trace(&self, free: Option<&[Lbool]>, inputs: &[Vec<Lbool>]) -> AigTrace {
+        let x = vec![Lbool::NONE; self.inputs.len()];
+        let state = self.init_state(free, inputs.first().unwrap_or(&x));
+        let mut sim = TernarySimulate::new(self, state);
+        let mut trace = AigTrace::default();
+        for input in inputs {
+            trace.latchs.push(sim.state().to_vec());
+            sim.simulate(input.clone());
+            trace.inputs.push(input.clone());
+            trace
+                .outputs
+                .push(self.outputs.iter().map(|e| sim.value(*e)).collect());
+            trace
+                .bads
+                .push(self.bads.iter().map(|e| sim.value(*e)).collect());
+            trace
+                .constraints
+                .push(self.constraints.iter().map(|e| sim.value(*e)).collect());
+        }
+        trace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AigEdge;
+
+    #[test]
+    fn test_simulate_trace() {
+        let mut aig = Aig::new();
+        let i: AigEdge = aig.new_input().into();
+        let u = aig.new_leaf_node();
+        let g = aig.new_leaf_node();
+        let c = aig.new_leaf_node();
+        let init = aig.new_and_node(u.into(), i);
+        aig.add_latch(u, u.into(), None);
+        aig.add_latch(g, g.into(), Some(init));
+        aig.add_latch(c, c.into(), Some(AigEdge::constant(true)));
+        aig.constraints.push(i);
+        aig.bads.push(u.into());
+        let free = [Lbool::TRUE, Lbool::NONE, Lbool::NONE];
+        assert_eq!(
+            aig.init_state(Some(&free), &[Lbool::FALSE]),
+            vec![Lbool::TRUE, Lbool::FALSE, Lbool::TRUE]
+        );
+        assert_eq!(
+            aig.init_state(None, &[Lbool::TRUE]),
+            vec![Lbool::NONE, Lbool::NONE, Lbool::TRUE]
+        );
+        let trace = aig.simulate_trace(Some(&free), &[vec![Lbool::FALSE]]);
+        assert_eq!(
+            trace.latchs[0],
+            vec![Lbool::TRUE, Lbool::FALSE, Lbool::TRUE]
+        );
+        assert_eq!(trace.bads[0], vec![Lbool::TRUE]);
+        assert_eq!(
+            trace.first_event(),
+            Some(AigSimEvent::ConstraintViolated {
+                cycle: 0,
+                constraint: 0
+            })
+        );
+        let trace = aig.simulate_trace(Some(&free), &[vec![Lbool::TRUE]]);
+        assert_eq!(trace.latchs[0][1], Lbool::TRUE);
+        assert_eq!(
+            trace.first_event(),
+            Some(AigSimEvent::BadReached { cycle: 0, bad: 0 })
+        );
+    }
+}
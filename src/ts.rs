@@ -0,0 +1,147 @@
+use crate::Aig;
+use giputils::hash::GHashMap;
+use logicrs::{Lit, LitVec, Var};
+
+/// CNF view of the sequential behaviour of an `Aig`. Node `n` is variable
+/// `n`, with variable 0 fixed to false by `trans`, and latch `k` has the
+/// primed variable `num_nodes + k` holding its next-state value.
+#[derive(Debug, Clone)]
+pub struct TransitionSystem {
+    pub inputs: Vec<Var>,
+    pub latchs: Vec<Var>,
+    /// Primed variable of each latch.
+    pub primed: Vec<Var>,
+    /// Next-state literal of each latch.
+    pub next: Vec<Lit>,
+    /// Latches with a constant init, as a cube over latch variables.
+    pub init: LitVec,
+    /// Latches with a gate-defined init, each equal to its init literal.
+    /// Asserted together with `trans` in the initial frame.
+    pub init_cnf: Vec<LitVec>,
+    /// The combinational logic and the relation between each primed
+    /// variable and its next-state literal.
+    pub trans: Vec<LitVec>,
+    pub constraints: Vec<Lit>,
+    pub bads: Vec<Lit>,
+    num_var: usize,
+    latch_idx: GHashMap<Var, usize>,
+    primed_idx: GHashMap<Var, usize>,
+}
+
+impl TransitionSystem {
+    #[inline]
+    pub fn num_var(&self) -> usize {
+        self.num_var
+    }
+
+    /// Index of the latch of a current-state variable.
+    #[inline]
+    pub fn latch_index(&self, v: Var) -> Option<usize> {
+        self.latch_idx.get(&v).copied()
+    }
+
+    /// Maps a current-state latch literal to its primed literal.
+    #[inline]
+    pub fn lit_next(&self, l: Lit) -> Option<Lit> {
+        let k = self.latch_index(l.var())?;
+        Some(self.primed[k].lit().not_if(!l.polarity()))
+    }
+
+    /// Maps a primed literal back to its current-state latch literal.
+    #[inline]
+    pub fn lit_prev(&self, l: Lit) -> Option<Lit> {
+        let k = *self.primed_idx.get(&l.var())?;
+        Some(self.latchs[k].lit().not_if(!l.polarity()))
+    }
+}
+
+impl Aig {
+    pub fn transition_system(&self) -> TransitionSystem {
+        let cnf = self.cnf(false);
+        let num_nodes = self.num_nodes();
+        let inputs = self.inputs.iter().map(|i| Var::new(*i)).collect();
+        let latchs: Vec<Var> = self.latchs.iter().map(|l| Var::new(l.input)).collect();
+        let primed: Vec<Var> = (0..latchs.len()).map(|k| Var::new(num_nodes + k)).collect();
+        let next: Vec<Lit> = self.latchs.iter().map(|l| l.next.to_lit()).collect();
+        let mut trans: Vec<LitVec> = cnf.clause().cloned().collect();
+        for (p, n) in primed.iter().zip(next.iter()) {
+            trans.push(LitVec::from([!p.lit(), *n]));
+            trans.push(LitVec::from([p.lit(), !*n]));
+        }
+        let mut init = LitVec::new();
+        let mut init_cnf = Vec::new();
+        for (l, v) in self.latchs.iter().zip(latchs.iter()) {
+            match l.init {
+                Some(i) if i.is_const() => init.push(v.lit().not_if(!i.to_constant())),
+                Some(i) => {
+                    init_cnf.push(LitVec::from([!v.lit(), i.to_lit()]));
+                    init_cnf.push(LitVec::from([v.lit(), !i.to_lit()]));
+                }
+                None => (),
+            }
+        }
+        let mut latch_idx = GHashMap::new();
+        let mut primed_idx = GHashMap::new();
+        for (k, (l, p)) in latchs.iter().zip(primed.iter()).enumerate() {
+            latch_idx.insert(*l, k);
+            primed_idx.insert(*p, k);
+        }
+        TransitionSystem {
+            inputs,
+            latchs,
+            primed,
+            next,
+            init,
+            init_cnf,
+            trans,
+            constraints: self.constraints.iter().map(|c| c.to_lit()).collect(),
+            bads: self.bads.iter().map(|b| b.to_lit()).collect(),
+            num_var: num_nodes + self.latchs.len(),
+            latch_idx,
+            primed_idx,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AigEdge, AigSatSolver, SimpleSatSolver};
+
+    #[test]
+    fn test_transition_system() {
+        let mut aig = Aig::new();
+        let i: AigEdge = aig.new_input().into();
+        let l0 = aig.new_leaf_node();
+        let l1 = aig.new_leaf_node();
+        let a = aig.new_and_node(i, l0.into());
+        let next = aig.new_and_node(!i, l1.into());
+        aig.add_latch(l0, next, Some(AigEdge::constant(false)));
+        aig.add_latch(l1, a, Some(a));
+        let ts = aig.transition_system();
+        assert_eq!(ts.init, LitVec::from([!Var::new(l0).lit()]));
+        assert_eq!(ts.init_cnf.len(), 2);
+        for l in [Var::new(l1).lit(), !Var::new(l1).lit()] {
+            let p = ts.lit_next(l).unwrap();
+            assert_eq!(p.var(), ts.primed[1]);
+            assert_eq!(ts.lit_prev(p), Some(l));
+        }
+        assert_eq!(ts.lit_next(i.to_lit()), None);
+        let mut solver = SimpleSatSolver::new();
+        solver.add_cnf(ts.trans.iter());
+        for k in 0..2 {
+            let p = ts.primed[k].lit();
+            assert!(solver.solve(&[p, ts.next[k]]));
+            assert!(!solver.solve(&[p, !ts.next[k]]));
+            assert!(!solver.solve(&[!p, ts.next[k]]));
+        }
+        solver.add_cnf(ts.init_cnf.iter());
+        let init: Vec<Lit> = ts
+            .init
+            .iter()
+            .copied()
+            .chain([Var::new(l1).lit()])
+            .collect();
+        assert!(!solver.solve(&init));
+    }
+}
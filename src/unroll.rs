@@ -0,0 +1,175 @@
+use crate::{Aig, AigEdge, AigNodeType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AigUnrollInit {
+    /// Latches take their `init` value in frame 0. Latches without one are
+    /// free, and gate-defined inits become frame 0 constraints.
+    #[default]
+    Init,
+    /// All latches are free in frame 0.
+    Free,
+}
+
+/// A combinational time-frame expansion of an `Aig`.
+#[derive(Debug, Clone)]
+pub struct AigUnroll {
+    /// The unrolled combinational aig. Its outputs, bads and constraints are
+    /// those of all frames, in frame order.
+    pub aig: Aig,
+    /// `frames[k][n]` is the edge of node `n` of the original aig in frame `k`.
+    pub frames: Vec<Vec<AigEdge>>,
+    pub bads: Vec<Vec<AigEdge>>,
+    pub constraints: Vec<Vec<AigEdge>>,
+}
+
+impl AigUnroll {
+    #[inline]
+    pub fn num_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Maps an edge of the original aig into frame `k`.
+    #[inline]
+    pub fn edge(&self, k: usize, e: AigEdge) -> AigEdge {
+        self.frames[k][e.node_id()].not_if(e.compl())
+    }
+}
+
+impl Aig {
+    /// Unrolls the aig into `k` time frames.
+    pub fn unroll_frames(&self, k: usize, init: AigUnrollInit) -> AigUnroll {
+        let mut res = Aig::new();
+        res.enable_strash();
+        let mut latch_idx = vec![None; self.num_nodes()];
+        for (i, l) in self.latchs.iter().enumerate() {
+            latch_idx[l.input] = Some(i);
+        }
+        let mut state: Vec<AigEdge> = self
+            .latchs
+            .iter()
+            .map(|l| match (init, l.init) {
+                (AigUnrollInit::Init, Some(i)) if i.is_const() => i,
+                _ => res.new_input().into(),
+            })
+            .collect();
+        let mut frames = Vec::with_capacity(k);
+        let mut frame_bads = Vec::with_capacity(k);
+        let mut frame_constraints = Vec::with_capacity(k);
+        for f in 0..k {
+            let mut map = vec![AigEdge::constant(false); self.num_nodes()];
+            for node in self.nodes.iter().skip(1) {
+                map[node.id] = match node.typ {
+                    AigNodeType::And(fanin0, fanin1) => {
+                        let fanin0 = map[fanin0.node_id()].not_if(fanin0.compl());
+                        let fanin1 = map[fanin1.node_id()].not_if(fanin1.compl());
+                        res.new_and_node(fanin0, fanin1)
+                    }
+                    _ => match latch_idx[node.id] {
+                        Some(l) => state[l],
+                        None => res.new_input().into(),
+                    },
+                };
+            }
+            let edge_map = |e: &AigEdge| map[e.node_id()].not_if(e.compl());
+            let mut constraints: Vec<AigEdge> = self.constraints.iter().map(edge_map).collect();
+            if f == 0 && init == AigUnrollInit::Init {
+                for l in self.latchs.iter() {
+                    if let Some(i) = l.init
+                        && !i.is_const()
+                    {
+                        let eq = res.new_eq_node(map[l.input], edge_map(&i));
+                        constraints.push(eq);
+                    }
+                }
+            }
+            let bads: Vec<AigEdge> = self.bads.iter().map(edge_map).collect();
+            res.outputs.extend(self.outputs.iter().map(edge_map));
+            res.bads.extend(bads.iter().copied());
+            res.constraints.extend(constraints.iter().copied());
+            state = self.latchs.iter().map(|l| edge_map(&l.next)).collect();
+            frame_bads.push(bads);
+            frame_constraints.push(constraints);
+            frames.push(map);
+        }
+        res.disable_strash();
+        AigUnroll {
+            aig: res,
+            frames,
+            bads: frame_bads,
+            constraints: frame_constraints,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use logicrs::Lbool;
+
+    fn check(aig: &Aig, k: usize, init: AigUnrollInit) {
+        let un = aig.unroll_frames(k, init);
+        assert_eq!(un.num_frames(), k);
+        let mut sim_aig = aig.clone();
+        if init == AigUnrollInit::Free {
+            for l in sim_aig.latchs.iter_mut() {
+                l.init = None;
+            }
+        }
+        let num_free = sim_aig.latchs.iter().filter(|l| l.init.is_none()).count();
+        let bits = k * aig.inputs.len() + num_free;
+        for m in 0..1usize << bits {
+            let v = |b: usize| Lbool::from(m >> b & 1 == 1);
+            let frames: Vec<Vec<Lbool>> = (0..k)
+                .map(|f| {
+                    (0..aig.inputs.len())
+                        .map(|i| v(f * aig.inputs.len() + i))
+                        .collect()
+                })
+                .collect();
+            let free: Vec<Lbool> = (0..aig.latchs.len())
+                .map(|l| v(k * aig.inputs.len() + l))
+                .collect();
+            let trace = sim_aig.simulate_trace(Some(&free), &frames);
+            let mut input = vec![Lbool::NONE; un.aig.inputs.len()];
+            let mut set = |e: AigEdge, val: Lbool| {
+                if let Some(p) = un.aig.inputs.iter().position(|n| *n == e.node_id()) {
+                    input[p] = val.not_if(e.compl());
+                }
+            };
+            for (f, frame) in frames.iter().enumerate() {
+                for (i, val) in aig.inputs.iter().zip(frame.iter()) {
+                    set(un.edge(f, (*i).into()), *val);
+                }
+            }
+            for (l, val) in aig.latchs.iter().zip(free.iter()) {
+                set(un.edge(0, l.input.into()), *val);
+            }
+            let value = un.aig.ternary_simulate(&input, &[]);
+            let eval = |e: AigEdge| value[e.node_id()].not_if(e.compl());
+            for f in 0..k {
+                for (l, val) in aig.latchs.iter().zip(trace.latchs[f].iter()) {
+                    assert_eq!(eval(un.edge(f, l.input.into())), *val);
+                }
+                assert_eq!(eval(un.bads[f][0]), trace.bads[f][0]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unroll_frames() {
+        // A two bit counter counting when its input is set.
+        let mut aig = Aig::new();
+        let en: AigEdge = aig.new_input().into();
+        let b0 = aig.new_leaf_node();
+        let b1 = aig.new_leaf_node();
+        let n0 = !aig.new_eq_node(b0.into(), en);
+        let carry = aig.new_and_node(b0.into(), en);
+        let n1 = !aig.new_eq_node(b1.into(), carry);
+        aig.add_latch(b0, n0, Some(AigEdge::constant(false)));
+        aig.add_latch(b1, n1, Some(AigEdge::constant(false)));
+        let bad = aig.new_and_node(b0.into(), b1.into());
+        aig.bads.push(bad);
+        check(&aig, 4, AigUnrollInit::Init);
+        check(&aig, 3, AigUnrollInit::Free);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{Aig, AigEdge, AigNodeType, AigPropKind};
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AigLocation {
+    Node(usize),
+    Input(usize),
+    Latch(usize),
+    Prop(AigPropKind, usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AigViolation {
+    /// `nodes[index]` carries a different id.
+    IdMismatch { index: usize, id: usize },
+    /// Node 0 is not the constant node, or the constant node appears elsewhere.
+    MisplacedFalse { index: usize },
+    /// An edge references a node that does not exist.
+    DanglingEdge { loc: AigLocation, edge: AigEdge },
+    /// An and node has a fanin that is not ordered before it.
+    NotTopological { node: usize, fanin: AigEdge },
+    /// An input or latch does not refer to a leaf node.
+    NotLeaf { loc: AigLocation, id: usize },
+    /// A leaf node is neither an input nor a latch.
+    UndrivenLeaf { id: usize },
+    /// A leaf node is declared as an input or latch more than once.
+    DuplicateLeaf { id: usize },
+}
+
+impl Display for AigViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AigViolation::IdMismatch { index, id } => {
+                write!(f, "node at index {index} has id {id}")
+            }
+            AigViolation::MisplacedFalse { index } => {
+                write!(f, "misplaced constant node at index {index}")
+            }
+            AigViolation::DanglingEdge { loc, edge } => {
+                write!(f, "{loc:?} references missing node {}", edge.node_id())
+            }
+            AigViolation::NotTopological { node, fanin } => {
+                write!(
+                    f,
+                    "and node {node} has fanin {} not ordered before it",
+                    fanin.node_id()
+                )
+            }
+            AigViolation::NotLeaf { loc, id } => write!(f, "{loc:?} refers to non-leaf node {id}"),
+            AigViolation::UndrivenLeaf { id } => {
+                write!(f, "leaf node {id} is neither an input nor a latch")
+            }
+            AigViolation::DuplicateLeaf { id } => {
+                write!(f, "leaf node {id} is declared more than once")
+            }
+        }
+    }
+}
+
+impl Aig {
+    /// Checks the structural invariants assumed by the passes on `Aig` and
+    /// returns all violations found.
+    pub fn validate(&self) -> Vec<AigViolation> {
+        let mut res = Vec::new();
+        let num_nodes = self.num_nodes();
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node.id != index {
+                res.push(AigViolation::IdMismatch { index, id: node.id });
+            }
+            match node.typ {
+                AigNodeType::False => {
+                    if index != 0 {
+                        res.push(AigViolation::MisplacedFalse { index });
+                    }
+                }
+                _ if index == 0 => res.push(AigViolation::MisplacedFalse { index }),
+                AigNodeType::Leaf => (),
+                AigNodeType::And(fanin0, fanin1) => {
+                    for fanin in [fanin0, fanin1] {
+                        if fanin.node_id() >= num_nodes {
+                            res.push(AigViolation::DanglingEdge {
+                                loc: AigLocation::Node(index),
+                                edge: fanin,
+                            });
+                        } else if fanin.node_id() >= index {
+                            res.push(AigViolation::NotTopological { node: index, fanin });
+                        }
+                    }
+                }
+            }
+        }
+        let mut declared = vec![false; num_nodes];
+        let leaves = self
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (AigLocation::Input(i), *id))
+            .chain(
+                self.latchs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, l)| (AigLocation::Latch(i), l.input)),
+            );
+        for (loc, id) in leaves {
+            if id >= num_nodes || !self.nodes[id].is_leaf() {
+                res.push(AigViolation::NotLeaf { loc, id });
+            } else if declared[id] {
+                res.push(AigViolation::DuplicateLeaf { id });
+            } else {
+                declared[id] = true;
+            }
+        }
+        for (id, node) in self.nodes.iter().enumerate() {
+            if node.is_leaf() && !declared[id] {
+                res.push(AigViolation::UndrivenLeaf { id });
+            }
+        }
+        let mut edges = Vec::new();
+        for (i, l) in self.latchs.iter().enumerate() {
+            edges.push((AigLocation::Latch(i), l.next));
+            edges.extend(l.init.map(|init| (AigLocation::Latch(i), init)));
+        }
+        for (kind, props) in [
+            (AigPropKind::Output, &self.outputs),
+            (AigPropKind::Bad, &self.bads),
+            (AigPropKind::Constraint, &self.constraints),
+            (AigPropKind::Fairness, &self.fairness),
+        ] {
+            edges.extend(
+                props
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| (AigLocation::Prop(kind, i), *e)),
+            );
+        }
+        for (i, j) in self.justice.iter().enumerate() {
+            edges.extend(
+                j.iter()
+                    .map(|e| (AigLocation::Prop(AigPropKind::Justice, i), *e)),
+            );
+        }
+        for (loc, edge) in edges {
+            if edge.node_id() >= num_nodes {
+                res.push(AigViolation::DanglingEdge { loc, edge });
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AigNode;
+
+    #[test]
+    fn test_validate() {
+        let aig = Aig::read_aiger(&b"aag 3 1 1 1 1\n2\n4 6\n6\n6 2 4\n"[..]).unwrap();
+        assert!(aig.validate().is_empty());
+        let n = aig.num_nodes();
+        let check = |f: &dyn Fn(&mut Aig), v: AigViolation| {
+            let mut a = aig.clone();
+            f(&mut a);
+            assert_eq!(a.validate(), vec![v]);
+        };
+        check(
+            &|a| a.nodes[3].id = 5,
+            AigViolation::IdMismatch { index: 3, id: 5 },
+        );
+        check(
+            &|a| {
+                a.nodes.push(AigNode {
+                    id: n,
+                    typ: AigNodeType::False,
+                })
+            },
+            AigViolation::MisplacedFalse { index: n },
+        );
+        let edge = AigEdge::new(n, false);
+        check(
+            &|a| a.bads.push(edge),
+            AigViolation::DanglingEdge {
+                loc: AigLocation::Prop(AigPropKind::Bad, 0),
+                edge,
+            },
+        );
+        check(
+            &|a| {
+                a.nodes
+                    .push(AigNode::new_and(n, edge, AigEdge::new(1, false)))
+            },
+            AigViolation::NotTopological {
+                node: n,
+                fanin: edge,
+            },
+        );
+        check(
+            &|a| a.inputs.push(3),
+            AigViolation::NotLeaf {
+                loc: AigLocation::Input(1),
+                id: 3,
+            },
+        );
+        check(
+            &|a| {
+                a.new_leaf_node();
+            },
+            AigViolation::UndrivenLeaf { id: n },
+        );
+        check(&|a| a.inputs.push(1), AigViolation::DuplicateLeaf { id: 1 });
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{Aig, AigPropKind, AigTrace};
+use logicrs::Lbool;
+use std::io::{self, Write};
+
+struct VcdSignal {
+    name: String,
+    code: String,
+    /// Indices into the values of the scope, one per bit with the most
+    /// significant bit first. Missing bits of a bus are `None`.
+    bits: Vec<Option<usize>>,
+}
+
+/// Splits `name[3]` into `("name", 3)`.
+fn bus_bit(name: &str) -> Option<(&str, usize)> {
+    let base = name.strip_suffix(']')?;
+    let (base, idx) = base.rsplit_once('[')?;
+    if base.is_empty() {
+        return None;
+    }
+    Some((base, idx.parse().ok()?))
+}
+
+fn vcd_code(mut i: usize) -> String {
+    let mut res = String::new();
+    loop {
+        res.push((b'!' + (i % 94) as u8) as char);
+        i /= 94;
+        if i == 0 {
+            return res;
+        }
+        i -= 1;
+    }
+}
+
+fn vcd_value(v: Lbool) -> char {
+    match v {
+        Lbool::FALSE => '0',
+        Lbool::TRUE => '1',
+        _ => 'x',
+    }
+}
+
+/// Groups the named values of a scope into scalars and buses.
+fn vcd_signals(names: Vec<String>, next_code: &mut usize) -> Vec<VcdSignal> {
+    let mut res: Vec<VcdSignal> = Vec::new();
+    let mut buses: Vec<(String, Vec<(usize, usize)>)> = Vec::new();
+    for (i, name) in names.iter().enumerate() {
+        match bus_bit(name) {
+            Some((base, idx)) => match buses.iter_mut().find(|(b, _)| b == base) {
+                Some((_, bits)) => bits.push((idx, i)),
+                None => buses.push((base.to_string(), vec![(idx, i)])),
+            },
+            None => res.push(VcdSignal {
+                name: name.clone(),
+                code: String::new(),
+                bits: vec![Some(i)],
+            }),
+        }
+    }
+    for (name, bits) in buses {
+        // Only dense indices form a bus, so its width never exceeds the
+        // number of named bits.
+        let width = bits
+            .iter()
+            .try_fold(0, |w: usize, (idx, _)| Some(w.max(idx.checked_add(1)?)))
+            .filter(|w| *w <= bits.len());
+        let vbits = width.and_then(|width| {
+            let mut vbits = vec![None; width];
+            for (idx, i) in bits.iter() {
+                if vbits[width - 1 - idx].replace(*i).is_some() {
+                    return None;
+                }
+            }
+            Some(vbits)
+        });
+        match vbits {
+            Some(vbits) => res.push(VcdSignal {
+                name,
+                code: String::new(),
+                bits: vbits,
+            }),
+            // Sparse or clashing bit names cannot form a bus, keep them as
+            // scalars.
+            None => res.extend(bits.iter().map(|(_, i)| VcdSignal {
+                name: names[*i].clone(),
+                code: String::new(),
+                bits: vec![Some(*i)],
+            })),
+        }
+    }
+    for s in res.iter_mut() {
+        s.code = vcd_code(*next_code);
+        *next_code += 1;
+    }
+    res
+}
+
+impl AigTrace {
+    /// Dumps the trace in the VCD format with one timestep per cycle. Inputs,
+    /// latches, outputs and bads are named by their symbols, and bits named
+    /// `name[k]` are grouped into buses.
+    pub fn write_vcd(&self, aig: &Aig, mut w: impl Write) -> io::Result<()> {
+        let node_names = |leaves: Vec<usize>, prefix: &str| -> Vec<String> {
+            leaves
+                .iter()
+                .enumerate()
+                .map(|(k, id)| {
+                    aig.get_symbol(*id)
+                        .unwrap_or_else(|| format!("{prefix}{k}"))
+                })
+                .collect()
+        };
+        let prop_names = |kind: AigPropKind, len: usize, prefix: &str| -> Vec<String> {
+            (0..len)
+                .map(|k| {
+                    aig.get_prop_symbol(kind, k)
+                        .unwrap_or_else(|| format!("{prefix}{k}"))
+                })
+                .collect()
+        };
+        let mut next_code = 0;
+        let scopes = [
+            ("inputs", &self.inputs, node_names(aig.inputs.clone(), "i")),
+            (
+                "latches",
+                &self.latchs,
+                node_names(aig.latchs.iter().map(|l| l.input).collect(), "l"),
+            ),
+            (
+                "outputs",
+                &self.outputs,
+                prop_names(AigPropKind::Output, aig.outputs.len(), "o"),
+            ),
+            (
+                "bads",
+                &self.bads,
+                prop_names(AigPropKind::Bad, aig.bads.len(), "b"),
+            ),
+        ]
+        .map(|(scope, values, names)| (scope, values, vcd_signals(names, &mut next_code)));
+        writeln!(w, "$timescale 1ns $end")?;
+        writeln!(w, "$scope module top $end")?;
+        for (scope, _, signals) in scopes.iter() {
+            writeln!(w, "$scope module {scope} $end")?;
+            for s in signals.iter() {
+                let name = s.name.replace(char::is_whitespace, "_");
+                if s.bits.len() == 1 {
+                    writeln!(w, "$var wire 1 {} {name} $end", s.code)?;
+                } else {
+                    let msb = s.bits.len() - 1;
+                    writeln!(w, "$var wire {} {} {name} [{msb}:0] $end", msb + 1, s.code)?;
+                }
+            }
+            writeln!(w, "$upscope $end")?;
+        }
+        writeln!(w, "$upscope $end")?;
+        writeln!(w, "$enddefinitions $end")?;
+        let mut last: Vec<Vec<Option<String>>> = scopes
+            .iter()
+            .map(|(_, _, signals)| vec![None; signals.len()])
+            .collect();
+        for cycle in 0..self.len() {
+            writeln!(w, "#{cycle}")?;
+            for ((_, values, signals), last) in scopes.iter().zip(last.iter_mut()) {
+                let Some(values) = values.get(cycle) else {
+                    continue;
+                };
+                for (s, last) in signals.iter().zip(last.iter_mut()) {
+                    let v: String = s
+                        .bits
+                        .iter()
+                        .map(|b| {
+                            b.and_then(|b| values.get(b).copied())
+                                .map_or('x', vcd_value)
+                        })
+                        .collect();
+                    if last.as_ref() == Some(&v) {
+                        continue;
+                    }
+                    if s.bits.len() == 1 {
+                        writeln!(w, "{v}{}", s.code)?;
+                    } else {
+                        writeln!(w, "b{v} {}", s.code)?;
+                    }
+                    *last = Some(v);
+                }
+            }
+        }
+        writeln!(w, "#{}", self.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AigEdge;
+
+    #[test]
+    fn test_write_vcd() {
+        let mut aig = Aig::new();
+        let i0 = aig.new_input();
+        let i1 = aig.new_input();
+        aig.set_symbol(i0, "data[0]");
+        aig.set_symbol(i1, "data[1]");
+        let b = aig.new_and_node(i0.into(), AigEdge::from(i1));
+        aig.bads.push(b);
+        let inputs = vec![
+            vec![Lbool::TRUE, Lbool::FALSE],
+            vec![Lbool::TRUE, Lbool::TRUE],
+        ];
+        let trace = aig.simulate_trace(None, &inputs);
+        let mut vcd = Vec::new();
+        trace.write_vcd(&aig, &mut vcd).unwrap();
+        let vcd = String::from_utf8(vcd).unwrap();
+        assert!(vcd.contains("$var wire 2 ! data [1:0] $end"));
+        assert!(vcd.contains("#0\nb01 !\n0\"\n#1\nb11 !\n1\"\n#2"));
+        aig.set_symbol(i1, &format!("data[{}]", usize::MAX));
+        let mut vcd = Vec::new();
+        trace.write_vcd(&aig, &mut vcd).unwrap();
+        let vcd = String::from_utf8(vcd).unwrap();
+        assert!(vcd.contains("$var wire 1 ! data[0] $end"));
+    }
+}
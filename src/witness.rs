@@ -0,0 +1,347 @@
+use crate::{Aig, AigPropKind, AigTrace, AigerError};
+use giputils::hash::GHashMap;
+use logicrs::{Lbool, Var, VarVMap};
+use std::{
+    fmt::{self, Display},
+    io::{self, Read, Write},
+    str::FromStr,
+};
+
+/// A counterexample in the AIGER witness format.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AigWitness {
+    /// Violated properties, each either a bad or a justice property.
+    pub props: Vec<(AigPropKind, usize)>,
+    /// Initial latch values.
+    pub init: Vec<Lbool>,
+    /// Input values of each frame.
+    pub inputs: Vec<Vec<Lbool>>,
+}
+
+fn parse_values(line: &str, lineno: usize, byte: usize) -> Result<Vec<Lbool>, AigerError> {
+    line.bytes()
+        .map(|c| match c {
+            b'0' => Ok(Lbool::FALSE),
+            b'1' => Ok(Lbool::TRUE),
+            b'x' | b'X' => Ok(Lbool::NONE),
+            c => Err(AigerError::Parse {
+                line: lineno,
+                byte,
+                message: format!("invalid witness value {:?}", c as char),
+            }),
+        })
+        .collect()
+}
+
+impl FromStr for AigWitness {
+    type Err = AigerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut byte = 0;
+        let mut lines = s.split('\n').enumerate().map(|(i, l)| {
+            let b = byte;
+            byte += l.len() + 1;
+            (i + 1, b, l.strip_suffix('\r').unwrap_or(l))
+        });
+        let error = |line, byte, message: &str| AigerError::Parse {
+            line,
+            byte,
+            message: message.to_string(),
+        };
+        let mut next = |expect: &str| {
+            lines
+                .next()
+                .ok_or_else(|| error(0, s.len(), &format!("missing {expect}")))
+        };
+        let mut status = next("status line")?;
+        while status.2.starts_with('c') {
+            status = next("status line")?;
+        }
+        let (line, byte, status) = status;
+        if status.trim() != "1" {
+            return Err(error(line, byte, "witness status is not 1"));
+        }
+        let (line, byte, props) = next("property line")?;
+        let mut res = AigWitness::default();
+        for p in props.split_whitespace() {
+            let kind = match p.as_bytes()[0] {
+                b'b' => AigPropKind::Bad,
+                b'j' => AigPropKind::Justice,
+                _ => return Err(error(line, byte, "invalid property")),
+            };
+            let Ok(idx) = p[1..].parse() else {
+                return Err(error(line, byte, "invalid property index"));
+            };
+            res.props.push((kind, idx));
+        }
+        let (line, byte, init) = next("initial state line")?;
+        res.init = parse_values(init.trim(), line, byte)?;
+        loop {
+            let (line, byte, input) = next("'.' terminating the witness")?;
+            let input = input.trim();
+            if input == "." {
+                break;
+            }
+            res.inputs.push(parse_values(input, line, byte)?);
+        }
+        Ok(res)
+    }
+}
+
+impl Display for AigWitness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values = |f: &mut fmt::Formatter<'_>, vs: &[Lbool]| {
+            for v in vs {
+                let c = match *v {
+                    Lbool::FALSE => '0',
+                    Lbool::TRUE => '1',
+                    _ => 'x',
+                };
+                write!(f, "{c}")?;
+            }
+            writeln!(f)
+        };
+        writeln!(f, "1")?;
+        let props: Vec<String> = self
+            .props
+            .iter()
+            .map(|(kind, i)| match kind {
+                AigPropKind::Justice => format!("j{i}"),
+                _ => format!("b{i}"),
+            })
+            .collect();
+        writeln!(f, "{}", props.join(" "))?;
+        values(f, &self.init)?;
+        for i in self.inputs.iter() {
+            values(f, i)?;
+        }
+        writeln!(f, ".")
+    }
+}
+
+impl AigWitness {
+    /// Builds a witness for the bad `bad` from a simulated trace.
+    pub fn from_trace(trace: &AigTrace, bad: usize) -> Self {
+        Self {
+            props: vec![(AigPropKind::Bad, bad)],
+            init: trace.latchs.first().cloned().unwrap_or_default(),
+            inputs: trace.inputs.clone(),
+        }
+    }
+
+    pub fn read_witness(mut r: impl Read) -> Result<Self, AigerError> {
+        let mut s = String::new();
+        r.read_to_string(&mut s)?;
+        s.parse()
+    }
+
+    pub fn write_witness(&self, mut w: impl Write) -> io::Result<()> {
+        write!(w, "{self}")
+    }
+
+    /// Lifts a witness of `refined`, obtained from `origin.coi_refine()`
+    /// together with `restore`, back to the inputs and latches of `origin`.
+    /// Inputs and latches removed by the refinement become don't-cares,
+    /// except latches with a constant init which keep it.
+    pub fn lift(&self, refined: &Aig, origin: &Aig, restore: &VarVMap) -> Self {
+        let old_id = |id: usize| -> usize { restore[Var::new(id)].into() };
+        let mut inputs = GHashMap::new();
+        for (k, i) in refined.inputs.iter().enumerate() {
+            inputs.insert(old_id(*i), k);
+        }
+        let mut latchs = GHashMap::new();
+        for (k, l) in refined.latchs.iter().enumerate() {
+            latchs.insert(old_id(l.input), k);
+        }
+        let init = origin
+            .latchs
+            .iter()
+            .map(|l| match latchs.get(&l.input) {
+                Some(k) => self.init[*k],
+                None => match l.init {
+                    Some(init) if init.is_const() => Lbool::from(init.to_constant()),
+                    _ => Lbool::NONE,
+                },
+            })
+            .collect();
+        let frames = self
+            .inputs
+            .iter()
+            .map(|frame| {
+                origin
+                    .inputs
+                    .iter()
+                    .map(|i| inputs.get(i).map_or(Lbool::NONE, |k| frame[*k]))
+                    .collect()
+            })
+            .collect();
+        Self {
+            props: self.props.clone(),
+            init,
+            inputs: frames,
+        }
+    }
+}
+
+/// Reasons for `Aig::check_witness` to reject a witness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AigWitnessError {
+    /// The initial state does not have one value per latch.
+    InitLength { witness: usize, latchs: usize },
+    /// An input frame does not have one value per input.
+    InputLength {
+        frame: usize,
+        witness: usize,
+        inputs: usize,
+    },
+    /// The initial value of a latch differs from its constant init.
+    InitContradiction { latch: usize },
+    /// The witness claims a justice property, which cannot be replayed.
+    UnsupportedJustice { justice: usize },
+    /// The witness claims a bad that does not exist.
+    InvalidBad { bad: usize },
+    /// A constraint does not hold before any claimed bad is reached.
+    ConstraintViolated { cycle: usize, constraint: usize },
+    /// No claimed bad is reached.
+    BadNotReached,
+}
+
+impl Display for AigWitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AigWitnessError::InitLength { witness, latchs } => write!(
+                f,
+                "witness has {witness} initial latch values but the model has {latchs} latches"
+            ),
+            AigWitnessError::InputLength {
+                frame,
+                witness,
+                inputs,
+            } => write!(
+                f,
+                "frame {frame} of the witness has {witness} inputs but the model has {inputs}"
+            ),
+            AigWitnessError::InitContradiction { latch } => {
+                write!(f, "witness contradicts the initial value of latch {latch}")
+            }
+            AigWitnessError::UnsupportedJustice { justice } => {
+                write!(f, "justice property {justice} cannot be replayed")
+            }
+            AigWitnessError::InvalidBad { bad } => write!(f, "bad {bad} does not exist"),
+            AigWitnessError::ConstraintViolated { cycle, constraint } => {
+                write!(
+                    f,
+                    "constraint {constraint} is not satisfied in cycle {cycle}"
+                )
+            }
+            AigWitnessError::BadNotReached => write!(f, "no claimed bad is reached"),
+        }
+    }
+}
+
+impl std::error::Error for AigWitnessError {}
+
+impl Aig {
+    /// Replays a witness on the model, like `aigsim -w`. Input values `x` are
+    /// simulated as unknown, so they must not affect the outcome. Returns the
+    /// violated bad and the cycle in which it is reached, with all
+    /// constraints holding up to and including that cycle.
+    pub fn check_witness(&self, w: &AigWitness) -> Result<(usize, usize), AigWitnessError> {
+        if w.init.len() != self.latchs.len() {
+            return Err(AigWitnessError::InitLength {
+                witness: w.init.len(),
+                latchs: self.latchs.len(),
+            });
+        }
+        if let Some(frame) = w.inputs.iter().position(|i| i.len() != self.inputs.len()) {
+            return Err(AigWitnessError::InputLength {
+                frame,
+                witness: w.inputs[frame].len(),
+                inputs: self.inputs.len(),
+            });
+        }
+        for (latch, (l, v)) in self.latchs.iter().zip(w.init.iter()).enumerate() {
+            if let Some(init) = l.init
+                && init.is_const()
+                && *v != Lbool::NONE
+                && *v != Lbool::from(init.to_constant())
+            {
+                return Err(AigWitnessError::InitContradiction { latch });
+            }
+        }
+        let mut bads = Vec::new();
+        for (kind, idx) in w.props.iter() {
+            if *kind != AigPropKind::Bad {
+                return Err(AigWitnessError::UnsupportedJustice { justice: *idx });
+            }
+            if *idx >= self.bads.len() {
+                return Err(AigWitnessError::InvalidBad { bad: *idx });
+            }
+            bads.push(*idx);
+        }
+        if bads.is_empty() {
+            bads.extend(0..self.bads.len());
+        }
+        // Unlike `AigTrace::first_event`, which reports what definitely
+        // happens, a witness must prove the violation for every value of its
+        // `x` inputs, so an unknown constraint rejects it as well.
+        let trace = self.simulate_trace(Some(&w.init), &w.inputs);
+        for cycle in 0..trace.len() {
+            if let Some(constraint) = trace.constraints[cycle]
+                .iter()
+                .position(|v| *v != Lbool::TRUE)
+            {
+                return Err(AigWitnessError::ConstraintViolated { cycle, constraint });
+            }
+            if let Some(b) = bads.iter().find(|b| trace.bads[cycle][**b] == Lbool::TRUE) {
+                return Ok((*b, cycle));
+            }
+        }
+        Err(AigWitnessError::BadNotReached)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AigEdge;
+
+    #[test]
+    fn test_check_witness() {
+        let mut aig = Aig::new();
+        let i: AigEdge = aig.new_input().into();
+        let l = aig.new_leaf_node();
+        aig.add_latch(l, i, Some(AigEdge::constant(false)));
+        let b = aig.new_and_node(i, l.into());
+        aig.bads.push(b);
+        let w: AigWitness = "c comment\n1\nb0\n0\n1\n1\n.\n".parse().unwrap();
+        assert_eq!(w.props, vec![(AigPropKind::Bad, 0)]);
+        assert_eq!(w.to_string(), "1\nb0\n0\n1\n1\n.\n");
+        assert_eq!(aig.check_witness(&w), Ok((0, 1)));
+        let w: AigWitness = "1\nb0\n0\n1\nx\n.\n".parse().unwrap();
+        assert_eq!(aig.check_witness(&w), Err(AigWitnessError::BadNotReached));
+        let w: AigWitness = "1\nb0\n1\n1\n.\n".parse().unwrap();
+        assert_eq!(
+            aig.check_witness(&w),
+            Err(AigWitnessError::InitContradiction { latch: 0 })
+        );
+    }
+    #[test]
+    fn test_lift_witness() {
+        let mut aig = Aig::new();
+        aig.new_input();
+        let b: AigEdge = aig.new_input().into();
+        let l = aig.new_leaf_node();
+        aig.add_latch(l, b, Some(AigEdge::constant(false)));
+        aig.bads.push(l.into());
+        let (refined, restore) = aig.coi_refine();
+        assert_eq!(refined.inputs.len(), 1);
+        let w: AigWitness = "1\nb0\n0\n1\n0\n.\n".parse().unwrap();
+        assert_eq!(refined.check_witness(&w), Ok((0, 1)));
+        let lifted = w.lift(&refined, &aig, &restore);
+        assert_eq!(lifted.to_string(), "1\nb0\n0\nx1\nx0\n.\n");
+        assert_eq!(aig.check_witness(&lifted), Ok((0, 1)));
+        let trace = aig.simulate_trace(None, &lifted.inputs);
+        assert_eq!(AigWitness::from_trace(&trace, 0), lifted);
+    }
+}